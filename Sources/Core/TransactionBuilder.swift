@@ -164,6 +164,19 @@ extension COption_Coin: COption {
     }
 }
 
+extension COption_NativeScript: COption {
+    typealias Tag = COption_NativeScript_Tag
+    typealias Value = CCardano.NativeScript
+
+    func someTag() -> Tag {
+        Some_NativeScript
+    }
+
+    func noneTag() -> Tag {
+        None_NativeScript
+    }
+}
+
 public enum CoinSelectionStrategyCIP2 {
     case largestFirst
     case randomImprove
@@ -272,6 +285,7 @@ public struct TransactionBuilder {
     public let inputTypes: MockWitnessSet
     public let mint: Mint?
     public let mintScripts: NativeScripts?
+    public let nativeScripts: NativeScripts?
     
     init(transactionBuilder: CCardano.TransactionBuilder) {
         config = transactionBuilder.config
@@ -292,6 +306,7 @@ public struct TransactionBuilder {
             $0.copiedDictionary().mapValues { $0.bigInt }
         }
         mintScripts = transactionBuilder.mint_scripts.get()?.copied().map { $0.copied() }
+        nativeScripts = transactionBuilder.native_scripts.get()?.copied().map { $0.copied() }
     }
     
     public init(config: TransactionBuilderConfig) throws {
@@ -332,9 +347,11 @@ public struct TransactionBuilder {
         }
     }
     
-    public mutating func addScriptInput(hash: ScriptHash, input: TransactionInput, amount: Value) throws {
+    public mutating func addScriptInput(
+        hash: ScriptHash, nativeScript: NativeScript? = nil, input: TransactionInput, amount: Value
+    ) throws {
         self = try withCTransactionBuilder {
-            try $0.addScriptInput(hash: hash, input: input, amount: amount)
+            try $0.addScriptInput(hash: hash, nativeScript: nativeScript, input: input, amount: amount)
         }
     }
     
@@ -431,20 +448,25 @@ public struct TransactionBuilder {
                                     try mintScripts.withCOption(
                                         with: { try $0.withCArray(fn: $1) }
                                     ) { mintScripts in
-                                        try fn(CCardano.TransactionBuilder(
-                                            config: config,
-                                            inputs: inputs,
-                                            outputs: outputs,
-                                            fee: fee.cOption(),
-                                            ttl: ttl.cOption(),
-                                            certs: certs,
-                                            withdrawals: withdrawals,
-                                            auxiliary_data: auxiliaryData,
-                                            validity_start_interval: validityStartInterval.cOption(),
-                                            input_types: inputTypes,
-                                            mint: mint,
-                                            mint_scripts: mintScripts
-                                        ))
+                                        try nativeScripts.withCOption(
+                                            with: { try $0.withCArray(fn: $1) }
+                                        ) { nativeScripts in
+                                            try fn(CCardano.TransactionBuilder(
+                                                config: config,
+                                                inputs: inputs,
+                                                outputs: outputs,
+                                                fee: fee.cOption(),
+                                                ttl: ttl.cOption(),
+                                                certs: certs,
+                                                withdrawals: withdrawals,
+                                                auxiliary_data: auxiliaryData,
+                                                validity_start_interval: validityStartInterval.cOption(),
+                                                input_types: inputTypes,
+                                                mint: mint,
+                                                mint_scripts: mintScripts,
+                                                native_scripts: nativeScripts
+                                            ))
+                                        }
                                     }
                                 }
                             }
@@ -497,10 +519,18 @@ extension CCardano.TransactionBuilder {
         return transactionBuilder.owned()
     }
     
-    public func addScriptInput(hash: ScriptHash, input: TransactionInput, amount: Value) throws -> TransactionBuilder {
-        var transactionBuilder = try amount.withCValue { amount in
-            RustResult<Self>.wrap { result, error in
-                cardano_transaction_builder_add_script_input(self, hash, input, amount, result, error)
+    public func addScriptInput(
+        hash: ScriptHash, nativeScript: NativeScript?, input: TransactionInput, amount: Value
+    ) throws -> TransactionBuilder {
+        var transactionBuilder = try nativeScript.withCOption(
+            with: { try $0.withCNativeScript(fn: $1) }
+        ) { nativeScript in
+            amount.withCValue { amount in
+                RustResult<Self>.wrap { result, error in
+                    cardano_transaction_builder_add_script_input(
+                        self, hash, nativeScript, input, amount, result, error
+                    )
+                }
             }
         }.get()
         return transactionBuilder.owned()
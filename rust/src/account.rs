@@ -0,0 +1,297 @@
+use crate::address::address::Address;
+use crate::address::base::BaseAddress;
+use crate::address::enterprise::EnterpriseAddress;
+use crate::address::reward::RewardAddress;
+use crate::array::CArray;
+use crate::bip32_private_key::Bip32PrivateKey;
+use crate::bip32_public_key::Bip32PublicKey;
+use crate::cip5;
+use crate::derivation_path::{KeyRole, HARDENED_INDEX};
+use crate::error::CError;
+use crate::network_info::NetworkInfo;
+use crate::panic::*;
+use crate::ptr::*;
+use crate::stake_credential::Ed25519KeyHash;
+use crate::string::{CharPtr, IntoCString};
+use cardano_serialization_lib::{
+  address::{
+    Address as RAddress, BaseAddress as RBaseAddress, EnterpriseAddress as REnterpriseAddress,
+    NetworkInfo as RNetworkInfo, RewardAddress as RRewardAddress,
+    StakeCredential as RStakeCredential,
+  },
+  crypto::{Bip32PrivateKey as RBip32PrivateKey, Bip32PublicKey as RBip32PublicKey},
+};
+use std::collections::HashSet;
+use std::convert::{TryFrom, TryInto};
+use zeroize::Zeroizing;
+
+pub const DEFAULT_GAP_LIMIT: u32 = 20;
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct DerivedPublicKey {
+  key: Bip32PublicKey,
+  hash: Ed25519KeyHash,
+}
+
+impl TryFrom<RBip32PublicKey> for DerivedPublicKey {
+  type Error = CError;
+
+  fn try_from(key: RBip32PublicKey) -> Result<Self> {
+    let hash = key.to_raw_key().hash().try_into()?;
+    Ok(Self { key: key.into(), hash })
+  }
+}
+
+// account / role / index, soft derivation only.
+fn derive_role(account: Bip32PublicKey, role: KeyRole, index: u32) -> Result<RBip32PublicKey> {
+  let account: RBip32PublicKey = account.try_into()?;
+  account
+    .derive(role as u32)
+    .and_then(|chain| chain.derive(index))
+    .into_result()
+}
+
+fn credential(account: Bip32PublicKey, role: KeyRole, index: u32) -> Result<RStakeCredential> {
+  derive_role(account, role, index)
+    .map(|key| RStakeCredential::from_keyhash(&key.to_raw_key().hash()))
+}
+
+fn payment_credential(account: Bip32PublicKey, role: KeyRole, index: u32) -> Result<RStakeCredential> {
+  match role {
+    KeyRole::External | KeyRole::Internal => credential(account, role, index),
+    KeyRole::Staking => Err("Staking keys cannot be used as payment credentials".into()),
+  }
+}
+
+fn network_id(network: NetworkInfo) -> u8 {
+  RNetworkInfo::from(network).network_id()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_bip32_public_key_account_external(
+  account: Bip32PublicKey, index: u32, result: &mut DerivedPublicKey, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    derive_role(account, KeyRole::External, index).and_then(|key| key.try_into())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_bip32_public_key_account_internal(
+  account: Bip32PublicKey, index: u32, result: &mut DerivedPublicKey, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    derive_role(account, KeyRole::Internal, index).and_then(|key| key.try_into())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_bip32_public_key_account_staking(
+  account: Bip32PublicKey, index: u32, result: &mut DerivedPublicKey, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    derive_role(account, KeyRole::Staking, index).and_then(|key| key.try_into())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_bip32_public_key_account_base_address(
+  account: Bip32PublicKey, network: NetworkInfo, role: KeyRole, index: u32, staking_index: u32,
+  result: &mut BaseAddress, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    payment_credential(account, role, index)
+      .zip(credential(account, KeyRole::Staking, staking_index))
+      .and_then(|(payment, stake)| {
+        RBaseAddress::new(network_id(network), &payment, &stake).try_into()
+      })
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_bip32_public_key_account_enterprise_address(
+  account: Bip32PublicKey, network: NetworkInfo, role: KeyRole, index: u32,
+  result: &mut EnterpriseAddress, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    payment_credential(account, role, index)
+      .and_then(|payment| REnterpriseAddress::new(network_id(network), &payment).try_into())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_bip32_public_key_account_reward_address(
+  account: Bip32PublicKey, network: NetworkInfo, staking_index: u32, result: &mut RewardAddress,
+  error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    credential(account, KeyRole::Staking, staking_index)
+      .and_then(|stake| RRewardAddress::new(network_id(network), &stake).try_into())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_bip32_public_key_to_account_bech32(
+  key: Bip32PublicKey, result: &mut CharPtr, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    key
+      .try_into()
+      .and_then(|key: RBip32PublicKey| cip5::encode(cip5::ACCT_XVK, &key.as_bytes()))
+      .map(|bech32| bech32.into_cstr())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_bip32_public_key_from_account_bech32(
+  bech32: CharPtr, result: &mut Bip32PublicKey, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    bech32
+      .unowned()
+      .and_then(|bech32| cip5::decode(cip5::ACCT_XVK, bech32))
+      .and_then(|bytes| RBip32PublicKey::from_bytes(&bytes).into_result())
+      .map(|key| key.into())
+  })
+  .response(result, error)
+}
+
+fn private_to_bech32(hrp: &str, key: Bip32PrivateKey) -> Result<CharPtr> {
+  key
+    .try_into()
+    .and_then(|key: RBip32PrivateKey| cip5::encode(hrp, &Zeroizing::new(key.as_bytes())))
+    .map(|bech32| bech32.into_cstr())
+}
+
+fn private_from_bech32(hrp: &str, bech32: CharPtr) -> Result<Bip32PrivateKey> {
+  unsafe { bech32.unowned() }
+    .and_then(|bech32| cip5::decode(hrp, bech32))
+    .map(Zeroizing::new)
+    .and_then(|bytes| RBip32PrivateKey::from_bytes(&bytes).into_result())
+    .map(|key| key.into())
+}
+
+// The bech32 string holds the secret and is not wiped by cardano_charptr_free.
+#[no_mangle]
+pub unsafe extern "C" fn cardano_bip32_private_key_to_account_bech32(
+  key: Bip32PrivateKey, result: &mut CharPtr, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| private_to_bech32(cip5::ACCT_XSK, key)).response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_bip32_private_key_from_account_bech32(
+  bech32: CharPtr, result: &mut Bip32PrivateKey, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| private_from_bech32(cip5::ACCT_XSK, bech32)).response(result, error)
+}
+
+// The bech32 string holds the secret and is not wiped by cardano_charptr_free.
+#[no_mangle]
+pub unsafe extern "C" fn cardano_bip32_private_key_to_root_bech32(
+  key: Bip32PrivateKey, result: &mut CharPtr, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| private_to_bech32(cip5::ROOT_XSK, key)).response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_bip32_private_key_from_root_bech32(
+  bech32: CharPtr, result: &mut Bip32PrivateKey, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| private_from_bech32(cip5::ROOT_XSK, bech32)).response(result, error)
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct AddressDiscovery {
+  used_external: CArray<u32>,
+  used_internal: CArray<u32>,
+  next_external: u32,
+  next_internal: u32,
+  next_receive_address: BaseAddress,
+}
+
+impl Free for AddressDiscovery {
+  unsafe fn free(&mut self) {
+    self.used_external.free();
+    self.used_internal.free();
+  }
+}
+
+struct ChainDiscovery {
+  used: Vec<u32>,
+  next: u32,
+}
+
+// An index is used when either its base or its enterprise address is.
+fn discover_chain(
+  account: Bip32PublicKey, network: u8, role: KeyRole, stake: &RStakeCredential, gap_limit: u32,
+  used: &HashSet<Vec<u8>>,
+) -> Result<ChainDiscovery> {
+  let mut chain = ChainDiscovery { used: Vec::new(), next: 0 };
+  let mut index = 0;
+  while index - chain.next < gap_limit {
+    if index >= HARDENED_INDEX {
+      return Err("Address discovery ran out of soft derivation indices".into());
+    }
+    let payment = payment_credential(account, role, index)?;
+    let base = RBaseAddress::new(network, &payment, stake).to_address();
+    let enterprise = REnterpriseAddress::new(network, &payment).to_address();
+    if used.contains(&base.to_bytes()) || used.contains(&enterprise.to_bytes()) {
+      chain.used.push(index);
+      chain.next = index + 1;
+    }
+    index += 1;
+  }
+  Ok(chain)
+}
+
+fn discover(
+  account: Bip32PublicKey, network: NetworkInfo, staking_index: u32, gap_limit: u32,
+  used: &[Address],
+) -> Result<AddressDiscovery> {
+  let gap_limit = if gap_limit == 0 { DEFAULT_GAP_LIMIT } else { gap_limit };
+  let used = used
+    .iter()
+    .map(|address| RAddress::try_from(*address).map(|address| address.to_bytes()))
+    .collect::<Result<HashSet<Vec<u8>>>>()?;
+  let network = network_id(network);
+  let stake = credential(account, KeyRole::Staking, staking_index)?;
+  let external = discover_chain(account, network, KeyRole::External, &stake, gap_limit, &used)?;
+  let internal = discover_chain(account, network, KeyRole::Internal, &stake, gap_limit, &used)?;
+  let receive = payment_credential(account, KeyRole::External, external.next)?;
+  Ok(AddressDiscovery {
+    used_external: external.used.into(),
+    used_internal: internal.used.into(),
+    next_external: external.next,
+    next_internal: internal.next,
+    next_receive_address: RBaseAddress::new(network, &receive, &stake).try_into()?,
+  })
+}
+
+// A gap limit of 0 selects DEFAULT_GAP_LIMIT.
+#[no_mangle]
+pub unsafe extern "C" fn cardano_bip32_public_key_account_discover(
+  account: Bip32PublicKey, network: NetworkInfo, staking_index: u32, gap_limit: u32,
+  used: CArray<Address>, result: &mut AddressDiscovery, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    used
+      .unowned()
+      .and_then(|used| discover(account, network, staking_index, gap_limit, used))
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_address_discovery_free(discovery: &mut AddressDiscovery) {
+  discovery.free()
+}
@@ -1,11 +1,30 @@
 use crate::bip32_public_key::Bip32PublicKey;
 use crate::data::CData;
+use crate::derivation_path::DerivationPath;
 use crate::error::CError;
+use crate::legacy_daedalus_private_key::cbor_bytes;
+use crate::option::COption;
 use crate::panic::*;
 use crate::ptr::*;
 use crate::string::*;
-use cardano_serialization_lib::address::ByronAddress as RByronAddress;
+use cardano_serialization_lib::address::{ByronAddress as RByronAddress, NetworkInfo as RNetworkInfo};
+use cardano_serialization_lib::crypto::Bip32PublicKey as RBip32PublicKey;
+use cardano_serialization_lib::legacy_address::ExtendedAddr;
+use cbor_event::de::Deserializer;
+use cbor_event::se::Serializer;
+use cbor_event::{Len, Special, Type};
+use cryptoxide::chacha20poly1305::ChaCha20Poly1305;
+use cryptoxide::hmac::Hmac;
+use cryptoxide::pbkdf2::pbkdf2;
+use cryptoxide::sha2::Sha512;
+use ed25519_bip32::XPub;
 use std::convert::{TryFrom, TryInto};
+use std::io::Cursor;
+
+const HD_PAYLOAD_SALT: &[u8] = b"address-hashing";
+const HD_PAYLOAD_NONCE: &[u8] = b"serokellfore";
+const HD_PAYLOAD_ITERATIONS: u32 = 500;
+const HD_PAYLOAD_TAG_LENGTH: usize = 16;
 
 #[repr(C)]
 #[derive(Copy)]
@@ -153,3 +172,116 @@ pub unsafe extern "C" fn cardano_byron_address_clone(
 pub unsafe extern "C" fn cardano_byron_address_free(address: &mut ByronAddress) {
   address.free();
 }
+
+// The HD payload is the derivation path, as an indefinite CBOR array, encrypted
+// with ChaCha20-Poly1305 under a key stretched from the root public key.
+fn hd_payload_key(root_key: &RBip32PublicKey) -> [u8; 32] {
+  let mut mac = Hmac::new(Sha512::new(), &root_key.as_bytes());
+  let mut key = [0u8; 32];
+  pbkdf2(&mut mac, HD_PAYLOAD_SALT, HD_PAYLOAD_ITERATIONS, &mut key);
+  key
+}
+
+fn encrypt_derivation_path(root_key: &RBip32PublicKey, path: &[u32]) -> Result<Vec<u8>> {
+  let mut serializer = Serializer::new_vec();
+  serializer.write_array(Len::Indefinite)?;
+  for index in path {
+    serializer.write_unsigned_integer(*index as u64)?;
+  }
+  serializer.write_special(Special::Break)?;
+  let plain = serializer.finalize();
+  let mut payload = vec![0u8; plain.len()];
+  let mut tag = [0u8; HD_PAYLOAD_TAG_LENGTH];
+  ChaCha20Poly1305::new(&hd_payload_key(root_key), HD_PAYLOAD_NONCE, &[])
+    .encrypt(&plain, &mut payload, &mut tag);
+  payload.extend_from_slice(&tag);
+  Ok(payload)
+}
+
+fn decode_derivation_path(plain: &[u8]) -> Result<Vec<u32>> {
+  let mut raw = Deserializer::from(Cursor::new(plain));
+  let len = raw.array()?;
+  let mut path = Vec::new();
+  loop {
+    match len {
+      Len::Len(len) if path.len() as u64 == len => break,
+      Len::Indefinite if raw.cbor_type()? == Type::Special => {
+        raw.special()?;
+        break;
+      }
+      _ => {
+        let index = raw.unsigned_integer()?;
+        path.push(u32::try_from(index).map_err(|_| format!("Invalid derivation index {}", index))?);
+      }
+    }
+  }
+  Ok(path)
+}
+
+// None when the address has no HD payload or it belongs to another wallet.
+fn decrypt_derivation_path(
+  root_key: &RBip32PublicKey, address: &RByronAddress,
+) -> Result<Option<Vec<u32>>> {
+  let address = ExtendedAddr::try_from(address.to_bytes().as_slice())
+    .map_err(|err| CError::from(format!("{:?}", err)))?;
+  let payload = match address.attributes.derivation_path {
+    Some(attribute) => Deserializer::from(Cursor::new(attribute)).bytes()?,
+    None => return Ok(None),
+  };
+  if payload.len() < HD_PAYLOAD_TAG_LENGTH {
+    return Ok(None);
+  }
+  let (cipher, tag) = payload.split_at(payload.len() - HD_PAYLOAD_TAG_LENGTH);
+  let mut plain = vec![0u8; cipher.len()];
+  if !ChaCha20Poly1305::new(&hd_payload_key(root_key), HD_PAYLOAD_NONCE, &[])
+    .decrypt(cipher, &mut plain, tag)
+  {
+    return Ok(None);
+  }
+  decode_derivation_path(&plain).map(Some)
+}
+
+fn daedalus_from_key(
+  key: &RBip32PublicKey, root_key: &RBip32PublicKey, path: &[u32], protocol_magic: u32,
+) -> Result<RByronAddress> {
+  let payload = encrypt_derivation_path(root_key, path).and_then(|payload| cbor_bytes(&payload))?;
+  // Byron mainnet addresses omit the protocol magic.
+  let protocol_magic = Some(protocol_magic)
+    .filter(|magic| *magic != RNetworkInfo::mainnet().protocol_magic());
+  let xpub = XPub::from_slice(&key.as_bytes()).map_err(|err| CError::from(err.to_string()))?;
+  let mut attributes = ExtendedAddr::new_simple(&xpub, protocol_magic).attributes;
+  attributes.derivation_path = Some(payload);
+  let address = ExtendedAddr::new(&xpub, attributes);
+  RByronAddress::from_bytes(address.to_address().as_ref().to_vec()).into_result()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_byron_address_daedalus_from_key(
+  key: Bip32PublicKey, root_key: Bip32PublicKey, path: DerivationPath, protocol_magic: u32,
+  result: &mut ByronAddress, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    key
+      .try_into()
+      .zip(root_key.try_into())
+      .zip(path.unowned())
+      .and_then(|((key, root_key), path)| daedalus_from_key(&key, &root_key, path, protocol_magic))
+      .map(|byron_address| byron_address.into())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_byron_address_derivation_path(
+  address: ByronAddress, root_key: Bip32PublicKey, result: &mut COption<DerivationPath>,
+  error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    address
+      .try_into()
+      .zip(root_key.try_into())
+      .and_then(|(address, root_key)| decrypt_derivation_path(&root_key, &address))
+      .map(|path| path.map(|path| path.into()).into())
+  })
+  .response(result, error)
+}
@@ -0,0 +1,284 @@
+use super::address::Address;
+use super::pointer::Pointer;
+use crate::array::CArray;
+use crate::cip5;
+use crate::error::CError;
+use crate::option::COption;
+use crate::panic::*;
+use crate::ptr::*;
+use crate::string::{CharPtr, IntoCString};
+use cardano_serialization_lib::address::{
+  Address as RAddress, BaseAddress as RBaseAddress, ByronAddress as RByronAddress,
+  EnterpriseAddress as REnterpriseAddress, NetworkInfo as RNetworkInfo,
+  PointerAddress as RPointerAddress, RewardAddress as RRewardAddress,
+  StakeCredKind as RStakeCredKind, StakeCredential as RStakeCredential,
+};
+use cardano_serialization_lib::legacy_address::ExtendedAddr;
+use cbor_event::de::Deserializer;
+use cbor_event::se::{Serialize, Serializer};
+use std::convert::{TryFrom, TryInto};
+use std::io::Cursor;
+
+const BYRON_REDEEM_ADDR_TYPE: u64 = 2;
+const PREPROD_PROTOCOL_MAGIC: u32 = 1;
+const PREVIEW_PROTOCOL_MAGIC: u32 = 2;
+
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum AddressEncoding {
+  Bech32,
+  Base58,
+  Hex,
+}
+
+// Discriminants are the CIP-19 header type nibbles.
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum AddressStyle {
+  BaseKeyKey = 0,
+  BaseScriptKey = 1,
+  BaseKeyScript = 2,
+  BaseScriptScript = 3,
+  PointerKey = 4,
+  PointerScript = 5,
+  EnterpriseKey = 6,
+  EnterpriseScript = 7,
+  Byron = 8,
+  RewardKey = 14,
+  RewardScript = 15,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum CredentialKind {
+  Key,
+  Script,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct CredentialInspection {
+  kind: CredentialKind,
+  hash_hex: CharPtr,
+  hash_bech32: CharPtr,
+}
+
+impl Free for CredentialInspection {
+  unsafe fn free(&mut self) {
+    self.hash_hex.free();
+    self.hash_bech32.free();
+  }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct ByronInspection {
+  protocol_magic: COption<u32>,
+  has_derivation_path: bool,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct AddressInspection {
+  address: Address,
+  encoding: AddressEncoding,
+  header_type: u8,
+  network_id: u8,
+  style: AddressStyle,
+  payment: COption<CredentialInspection>,
+  stake: COption<CredentialInspection>,
+  pointer: COption<Pointer>,
+  byron: COption<ByronInspection>,
+  warnings: CArray<CharPtr>,
+}
+
+impl Free for AddressInspection {
+  unsafe fn free(&mut self) {
+    self.address.free();
+    self.payment.free();
+    self.stake.free();
+    self.warnings.free();
+  }
+}
+
+pub(crate) fn parse_address(address: &str) -> Result<(RAddress, AddressEncoding)> {
+  let address = address.trim();
+  if bech32::decode(address).is_ok() {
+    RAddress::from_bech32(address)
+      .into_result()
+      .map(|address| (address, AddressEncoding::Bech32))
+  } else if let Ok(byron) = RByronAddress::from_base58(address) {
+    Ok((byron.to_address(), AddressEncoding::Base58))
+  } else if let Ok(bytes) = hex::decode(address) {
+    RAddress::from_bytes(bytes)
+      .into_result()
+      .map(|address| (address, AddressEncoding::Hex))
+  } else {
+    Err(format!("Unrecognized address format \"{}\"", address).into())
+  }
+}
+
+// CIP-5 prefix, "addr" or "stake" with "_test" outside mainnet.
+pub(crate) fn bech32_prefix(address: &RAddress) -> Result<String> {
+  address
+    .to_bech32(None)
+    .into_result()
+    .map(|bech32| bech32[..bech32.rfind('1').unwrap_or(0)].to_string())
+}
+
+fn style_of(address: &RAddress) -> Result<AddressStyle> {
+  if RByronAddress::from_address(address).is_some() {
+    return Ok(AddressStyle::Byron);
+  }
+  let header = *address.to_bytes().first().ok_or("Empty address")?;
+  Ok(match header >> 4 {
+    0 => AddressStyle::BaseKeyKey,
+    1 => AddressStyle::BaseScriptKey,
+    2 => AddressStyle::BaseKeyScript,
+    3 => AddressStyle::BaseScriptScript,
+    4 => AddressStyle::PointerKey,
+    5 => AddressStyle::PointerScript,
+    6 => AddressStyle::EnterpriseKey,
+    7 => AddressStyle::EnterpriseScript,
+    14 => AddressStyle::RewardKey,
+    15 => AddressStyle::RewardScript,
+    other => return Err(format!("Unsupported address header type {}", other).into()),
+  })
+}
+
+fn inspect_credential(
+  credential: &RStakeCredential, key_prefix: &str,
+) -> Result<CredentialInspection> {
+  let (kind, hash, prefix) = match credential.kind() {
+    RStakeCredKind::Key => (
+      CredentialKind::Key,
+      credential.to_keyhash().map(|hash| hash.to_bytes()),
+      key_prefix,
+    ),
+    RStakeCredKind::Script => (
+      CredentialKind::Script,
+      credential.to_scripthash().map(|hash| hash.to_bytes()),
+      cip5::SCRIPT,
+    ),
+  };
+  let hash = hash.ok_or("Invalid credential")?;
+  Ok(CredentialInspection {
+    kind,
+    hash_hex: hex::encode(&hash).into_cstr(),
+    hash_bech32: cip5::encode(prefix, &hash)?.into_cstr(),
+  })
+}
+
+// The legacy address type is not exported, so it is read back from its CBOR form.
+fn byron_addr_type(address: &ExtendedAddr) -> Result<u64> {
+  let mut serializer = Serializer::new_vec();
+  address.addr_type.serialize(&mut serializer)?;
+  Ok(Deserializer::from(Cursor::new(serializer.finalize())).unsigned_integer()?)
+}
+
+// The network id follows the protocol magic, mainnet or any testnet.
+fn inspect_byron(
+  byron: &RByronAddress, warnings: &mut Vec<String>,
+) -> Result<(ByronInspection, u8)> {
+  let extended = ExtendedAddr::try_from(byron.to_bytes().as_slice())
+    .map_err(|err| CError::from(format!("{:?}", err)))?;
+  let protocol_magic = extended.attributes.protocol_magic;
+  let has_derivation_path = extended.attributes.derivation_path.is_some();
+  warnings.push("Legacy Byron address".to_string());
+  if byron_addr_type(&extended)? == BYRON_REDEEM_ADDR_TYPE {
+    warnings.push("Byron redeem (AVVM) address".to_string());
+  }
+  if has_derivation_path {
+    warnings.push(
+      "The encrypted derivation path links this address to its Daedalus wallet".to_string(),
+    );
+  }
+  let mainnet = RNetworkInfo::mainnet();
+  let network_id = match protocol_magic {
+    Some(magic) if magic != mainnet.protocol_magic() => RNetworkInfo::testnet().network_id(),
+    _ => mainnet.network_id(),
+  };
+  let known = [
+    mainnet.protocol_magic(),
+    RNetworkInfo::testnet().protocol_magic(),
+    PREPROD_PROTOCOL_MAGIC,
+    PREVIEW_PROTOCOL_MAGIC,
+  ];
+  if let Some(magic) = protocol_magic.filter(|magic| !known.contains(magic)) {
+    warnings.push(format!("Unknown Byron protocol magic {}", magic));
+  }
+  Ok((ByronInspection { protocol_magic: protocol_magic.into(), has_derivation_path }, network_id))
+}
+
+pub(crate) fn inspect(address: &str) -> Result<AddressInspection> {
+  let (raddress, encoding) = parse_address(address)?;
+  let style = style_of(&raddress)?;
+  let mut warnings = Vec::new();
+  let (mut payment, mut stake, mut pointer, mut byron) = (None, None, None, None);
+  let network_id = match RByronAddress::from_address(&raddress) {
+    Some(address) => {
+      let (inspection, network_id) = inspect_byron(&address, &mut warnings)?;
+      byron = Some(inspection);
+      network_id
+    }
+    None => raddress.network_id().into_result()?,
+  };
+  if network_id > 1 {
+    warnings.push(format!("Unknown network id {}", network_id));
+  }
+  if encoding == AddressEncoding::Bech32 && byron.is_none() {
+    let prefix = address.trim().rsplit_once('1').map(|(prefix, _)| prefix).unwrap_or("");
+    let expected = bech32_prefix(&raddress)?;
+    if prefix != expected {
+      warnings.push(format!(
+        "Bech32 prefix \"{}\" does not match the expected \"{}\"",
+        prefix, expected
+      ));
+    }
+  }
+
+  if let Some(base) = RBaseAddress::from_address(&raddress) {
+    payment = Some(inspect_credential(&base.payment_cred(), cip5::ADDR_VKH)?);
+    stake = Some(inspect_credential(&base.stake_cred(), cip5::STAKE_VKH)?);
+  } else if let Some(ptr) = RPointerAddress::from_address(&raddress) {
+    payment = Some(inspect_credential(&ptr.payment_cred(), cip5::ADDR_VKH)?);
+    pointer = Some(ptr.stake_pointer().into());
+    warnings.push("Pointer addresses are deprecated and no longer delegate stake".to_string());
+  } else if let Some(enterprise) = REnterpriseAddress::from_address(&raddress) {
+    payment = Some(inspect_credential(&enterprise.payment_cred(), cip5::ADDR_VKH)?);
+    warnings.push("Enterprise address, funds do not take part in staking".to_string());
+  } else if let Some(reward) = RRewardAddress::from_address(&raddress) {
+    stake = Some(inspect_credential(&reward.payment_cred(), cip5::STAKE_VKH)?);
+    warnings.push("Reward address, it cannot receive payments".to_string());
+  }
+
+  Ok(AddressInspection {
+    address: raddress.try_into()?,
+    encoding,
+    header_type: style as u8,
+    network_id,
+    style,
+    payment: payment.into(),
+    stake: stake.into(),
+    pointer: pointer.into(),
+    byron: byron.into(),
+    warnings: warnings
+      .into_iter()
+      .map(|warning| warning.into_cstr())
+      .collect::<Vec<CharPtr>>()
+      .into(),
+  })
+}
+
+// Accepts bech32, Byron base58 or hex encoded bytes.
+#[no_mangle]
+pub unsafe extern "C" fn cardano_address_inspect(
+  address: CharPtr, result: &mut AddressInspection, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| address.unowned().and_then(inspect)).response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_address_inspection_free(inspection: &mut AddressInspection) {
+  inspection.free()
+}
@@ -2,7 +2,10 @@ pub mod address;
 pub mod base;
 pub mod byron;
 pub mod enterprise;
+pub mod inspect;
 pub mod pointer;
 pub mod reward;
+pub mod script;
+pub mod validation;
 
 mod addr_type;
\ No newline at end of file
@@ -0,0 +1,132 @@
+use crate::address::base::BaseAddress;
+use crate::address::enterprise::EnterpriseAddress;
+use crate::address::reward::RewardAddress;
+use crate::error::CError;
+use crate::hash::blake2b;
+use crate::network_info::NetworkInfo;
+use crate::panic::*;
+use crate::stake_credential::{Ed25519KeyHash, ScriptHash};
+use crate::transaction_metadata::{NativeScript, PlutusScript};
+use cardano_serialization_lib::{
+  address::{
+    BaseAddress as RBaseAddress, EnterpriseAddress as REnterpriseAddress,
+    NetworkInfo as RNetworkInfo, RewardAddress as RRewardAddress,
+    StakeCredential as RStakeCredential,
+  },
+  crypto::ScriptHash as RScriptHash,
+  plutus::PlutusScript as RPlutusScript,
+  NativeScript as RNativeScript, ScriptHashNamespace as RScriptHashNamespace,
+};
+use std::convert::TryInto;
+
+// Ledger namespace tags prepended to the script bytes before hashing.
+const PLUTUS_V1_TAG: u8 = 1;
+const PLUTUS_V2_TAG: u8 = 2;
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub enum Script {
+  NativeScriptKind(NativeScript),
+  PlutusScriptV1Kind(PlutusScript),
+  PlutusScriptV2Kind(PlutusScript),
+}
+
+fn plutus_script_hash(script: PlutusScript, tag: u8) -> Result<RScriptHash> {
+  let script: RPlutusScript = script.try_into()?;
+  let mut bytes = vec![tag];
+  bytes.extend(script.bytes());
+  RScriptHash::from_bytes(blake2b::<224>(&bytes)).into_result()
+}
+
+pub(crate) fn script_hash(script: Script) -> Result<RScriptHash> {
+  match script {
+    Script::NativeScriptKind(native_script) => native_script
+      .try_into()
+      .map(|native_script: RNativeScript| native_script.hash(RScriptHashNamespace::NativeScript)),
+    Script::PlutusScriptV1Kind(plutus_script) => plutus_script_hash(plutus_script, PLUTUS_V1_TAG),
+    Script::PlutusScriptV2Kind(plutus_script) => plutus_script_hash(plutus_script, PLUTUS_V2_TAG),
+  }
+}
+
+fn script_credential(script: Script) -> Result<RStakeCredential> {
+  script_hash(script).map(|hash| RStakeCredential::from_scripthash(&hash))
+}
+
+fn key_credential(hash: Ed25519KeyHash) -> RStakeCredential {
+  RStakeCredential::from_keyhash(&hash.into())
+}
+
+fn base_address(
+  network: NetworkInfo, payment: RStakeCredential, stake: RStakeCredential,
+) -> Result<BaseAddress> {
+  RBaseAddress::new(RNetworkInfo::from(network).network_id(), &payment, &stake).try_into()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_script_hash(
+  script: Script, result: &mut ScriptHash, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| script_hash(script).and_then(|hash| hash.try_into()))
+    .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_script_enterprise_address(
+  script: Script, network: NetworkInfo, result: &mut EnterpriseAddress, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    script_credential(script).and_then(|payment| {
+      REnterpriseAddress::new(RNetworkInfo::from(network).network_id(), &payment).try_into()
+    })
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_script_reward_address(
+  script: Script, network: NetworkInfo, result: &mut RewardAddress, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    script_credential(script).and_then(|stake| {
+      RRewardAddress::new(RNetworkInfo::from(network).network_id(), &stake).try_into()
+    })
+  })
+  .response(result, error)
+}
+
+// Script payment credential, key stake credential.
+#[no_mangle]
+pub unsafe extern "C" fn cardano_script_base_address_key_stake(
+  script: Script, stake: Ed25519KeyHash, network: NetworkInfo, result: &mut BaseAddress,
+  error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    script_credential(script).and_then(|payment| base_address(network, payment, key_credential(stake)))
+  })
+  .response(result, error)
+}
+
+// Key payment credential, script stake credential.
+#[no_mangle]
+pub unsafe extern "C" fn cardano_script_base_address_key_payment(
+  payment: Ed25519KeyHash, script: Script, network: NetworkInfo, result: &mut BaseAddress,
+  error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    script_credential(script).and_then(|stake| base_address(network, key_credential(payment), stake))
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_script_base_address(
+  payment: Script, stake: Script, network: NetworkInfo, result: &mut BaseAddress,
+  error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    script_credential(payment)
+      .zip(script_credential(stake))
+      .and_then(|(payment, stake)| base_address(network, payment, stake))
+  })
+  .response(result, error)
+}
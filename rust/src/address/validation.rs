@@ -0,0 +1,232 @@
+use super::address::Address;
+use crate::error::CError;
+use crate::network_info::NetworkInfo;
+use crate::panic::*;
+use crate::ptr::*;
+use crate::string::{CharPtr, IntoCString};
+use bech32::FromBase32;
+use cardano_serialization_lib::address::{
+  Address as RAddress, ByronAddress as RByronAddress, NetworkInfo as RNetworkInfo,
+  RewardAddress as RRewardAddress,
+};
+use cardano_serialization_lib::legacy_address::ExtendedAddr;
+use cbor_event::de::Deserializer;
+use std::convert::{TryFrom, TryInto};
+use std::io::Cursor;
+
+const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const BYRON_PAYLOAD_TAG: u64 = 24;
+const MAINNET_NETWORK_ID: u8 = 1;
+
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum AddressPurpose {
+  Payment,
+  Reward,
+  Any,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct ValueMismatch<T> {
+  found: T,
+  expected: T,
+}
+
+impl<T: Free> Free for ValueMismatch<T> {
+  unsafe fn free(&mut self) {
+    self.found.free();
+    self.expected.free();
+  }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub enum AddressValidation {
+  ValidKind(Address),
+  InvalidFormatKind(CharPtr),
+  InvalidChecksumKind,
+  WrongPrefixKind(ValueMismatch<CharPtr>),
+  WrongNetworkIdKind(ValueMismatch<u8>),
+  WrongProtocolMagicKind(ValueMismatch<u32>),
+  RewardAddressKind,
+  NotRewardAddressKind,
+}
+
+impl Free for AddressValidation {
+  unsafe fn free(&mut self) {
+    match self {
+      AddressValidation::ValidKind(address) => address.free(),
+      AddressValidation::InvalidFormatKind(reason) => reason.free(),
+      AddressValidation::WrongPrefixKind(mismatch) => mismatch.free(),
+      _ => {}
+    }
+  }
+}
+
+fn invalid_format(reason: impl ToString) -> AddressValidation {
+  AddressValidation::InvalidFormatKind(reason.to_string().into_cstr())
+}
+
+fn base58_decode(string: &str) -> Option<Vec<u8>> {
+  let mut bytes: Vec<u8> = Vec::new();
+  for c in string.bytes() {
+    let mut carry = BASE58_ALPHABET.iter().position(|a| *a == c)? as u32;
+    for byte in bytes.iter_mut() {
+      carry += *byte as u32 * 58;
+      *byte = carry as u8;
+      carry >>= 8;
+    }
+    while carry > 0 {
+      bytes.push(carry as u8);
+      carry >>= 8;
+    }
+  }
+  bytes.extend(string.bytes().take_while(|c| *c == b'1').map(|_| 0));
+  bytes.reverse();
+  Some(bytes)
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+  !bytes.iter().fold(!0u32, |crc, byte| {
+    (0..8).fold(crc ^ *byte as u32, |crc, _| {
+      if crc & 1 == 1 { crc >> 1 ^ 0xEDB8_8320 } else { crc >> 1 }
+    })
+  })
+}
+
+// Byron addresses are [tag 24 (bytes payload), crc32 (payload)].
+fn byron_checksum_matches(bytes: &[u8]) -> cbor_event::Result<bool> {
+  let mut raw = Deserializer::from(Cursor::new(bytes));
+  raw.tuple(2, "Byron address")?;
+  if raw.tag()? != BYRON_PAYLOAD_TAG {
+    return Err(cbor_event::Error::CustomError("Byron payload is not tagged as CBOR".into()));
+  }
+  let payload = raw.bytes()?;
+  Ok(crc32(&payload) as u64 == raw.unsigned_integer()?)
+}
+
+fn expected_prefix(purpose: &str, network_id: u8) -> String {
+  if network_id == MAINNET_NETWORK_ID {
+    purpose.to_string()
+  } else {
+    format!("{}_test", purpose)
+  }
+}
+
+fn check_purpose(address: &RAddress, purpose: AddressPurpose) -> Option<AddressValidation> {
+  let is_reward = RRewardAddress::from_address(address).is_some();
+  match purpose {
+    AddressPurpose::Payment if is_reward => Some(AddressValidation::RewardAddressKind),
+    AddressPurpose::Reward if !is_reward => Some(AddressValidation::NotRewardAddressKind),
+    _ => None,
+  }
+}
+
+fn validate_shelley(
+  address: &str, network: &RNetworkInfo, purpose: AddressPurpose,
+) -> Result<AddressValidation> {
+  let (prefix, data) = match bech32::decode(address) {
+    Ok(decoded) => decoded,
+    Err(bech32::Error::InvalidChecksum) => return Ok(AddressValidation::InvalidChecksumKind),
+    Err(err) => return Ok(invalid_format(err)),
+  };
+  let raddress = match Vec::<u8>::from_base32(&data)
+    .map_err(|err| err.to_string())
+    .and_then(|bytes| RAddress::from_bytes(bytes).map_err(|err| format!("{:?}", err)))
+  {
+    Ok(raddress) => raddress,
+    Err(err) => return Ok(invalid_format(err)),
+  };
+  if RByronAddress::from_address(&raddress).is_some() {
+    return Ok(invalid_format("Byron addresses are not bech32 encoded"));
+  }
+  let kind = if RRewardAddress::from_address(&raddress).is_some() { "stake" } else { "addr" };
+  let expected = expected_prefix(kind, network.network_id());
+  // A prefix of the other network is reported as a network mismatch too.
+  let other_network_id =
+    if network.network_id() == MAINNET_NETWORK_ID { 0 } else { MAINNET_NETWORK_ID };
+  let network_id = match raddress.network_id().into_result()? {
+    _ if prefix == expected_prefix(kind, other_network_id) => other_network_id,
+    network_id => network_id,
+  };
+  if network_id != network.network_id() {
+    return Ok(AddressValidation::WrongNetworkIdKind(ValueMismatch {
+      found: network_id,
+      expected: network.network_id(),
+    }));
+  }
+  if prefix != expected {
+    return Ok(AddressValidation::WrongPrefixKind(ValueMismatch {
+      found: prefix.into_cstr(),
+      expected: expected.into_cstr(),
+    }));
+  }
+  match check_purpose(&raddress, purpose) {
+    Some(validation) => Ok(validation),
+    None => raddress.try_into().map(AddressValidation::ValidKind),
+  }
+}
+
+fn validate_byron(
+  address: &str, network: &RNetworkInfo, purpose: AddressPurpose,
+) -> Result<AddressValidation> {
+  let bytes = match base58_decode(address) {
+    Some(bytes) => bytes,
+    None => return Ok(invalid_format("Address is neither bech32 nor base58 encoded")),
+  };
+  match byron_checksum_matches(&bytes) {
+    Ok(true) => {}
+    Ok(false) => return Ok(AddressValidation::InvalidChecksumKind),
+    Err(err) => return Ok(invalid_format(format!("Invalid Byron address: {}", err))),
+  }
+  let extended = match ExtendedAddr::try_from(bytes.as_slice()) {
+    Ok(extended) => extended,
+    Err(err) => return Ok(invalid_format(format!("{:?}", err))),
+  };
+  // Mainnet Byron addresses carry no protocol magic.
+  let magic =
+    extended.attributes.protocol_magic.unwrap_or(RNetworkInfo::mainnet().protocol_magic());
+  if magic != network.protocol_magic() {
+    return Ok(AddressValidation::WrongProtocolMagicKind(ValueMismatch {
+      found: magic,
+      expected: network.protocol_magic(),
+    }));
+  }
+  let raddress = RByronAddress::from_bytes(bytes).into_result()?.to_address();
+  match check_purpose(&raddress, purpose) {
+    Some(validation) => Ok(validation),
+    None => raddress.try_into().map(AddressValidation::ValidKind),
+  }
+}
+
+pub(crate) fn validate(
+  address: &str, network: NetworkInfo, purpose: AddressPurpose,
+) -> Result<AddressValidation> {
+  let address = address.trim();
+  let network: RNetworkInfo = network.into();
+  // Bech32 may be all uppercase, as in QR codes.
+  let lowercase = address.to_lowercase();
+  if lowercase.starts_with("addr") || lowercase.starts_with("stake") {
+    let address = if address.to_uppercase() == address { &lowercase } else { address };
+    validate_shelley(address, &network, purpose)
+  } else {
+    validate_byron(address, &network, purpose)
+  }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_address_validate(
+  address: CharPtr, network: NetworkInfo, purpose: AddressPurpose, result: &mut AddressValidation,
+  error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    address.unowned().and_then(|address| validate(address, network, purpose))
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_address_validation_free(validation: &mut AddressValidation) {
+  validation.free()
+}
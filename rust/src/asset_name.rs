@@ -3,6 +3,7 @@ use super::ptr::*;
 use std::convert::{TryInto, TryFrom};
 use super::error::CError;
 use super::panic::*;
+use super::string::{CharPtr, IntoCString};
 use cardano_serialization_lib::{AssetName as RAssetName};
 
 #[repr(C)]
@@ -80,3 +81,20 @@ pub unsafe extern "C" fn cardano_asset_name_get_name(
     asset_name.try_into().map(|name: RAssetName| name.name().into())
   }).response(result, error)
 }
+
+// UTF-8 when every character is printable, hex otherwise.
+pub(crate) fn display_name(name: &[u8]) -> String {
+  match std::str::from_utf8(name) {
+    Ok(text) if !text.chars().any(char::is_control) => text.to_string(),
+    _ => hex::encode(name),
+  }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_asset_name_to_display_string(
+  asset_name: AssetName, result: &mut CharPtr, error: &mut CError
+) -> bool {
+  handle_exception_result(|| {
+    asset_name.try_into().map(|name: RAssetName| display_name(&name.name()).into_cstr())
+  }).response(result, error)
+}
@@ -7,22 +7,38 @@ use super::panic::*;
 use super::string::*;
 use super::bip32_public_key::Bip32PublicKey;
 use cardano_serialization_lib::crypto::{Bip32PrivateKey as RBip32PrivateKey};
+use bip39::Mnemonic as RMnemonic;
+use cryptoxide::digest::Digest;
+use cryptoxide::hmac::Hmac;
+use cryptoxide::mac::Mac;
+use cryptoxide::sha2::{Sha256, Sha512};
+use zeroize::{Zeroize, Zeroizing};
 
 #[repr(C)]
 #[derive(Copy, Clone)]
 pub struct Bip32PrivateKey([u8; 96]);
 
+impl Zeroize for Bip32PrivateKey {
+  fn zeroize(&mut self) {
+    self.0.zeroize()
+  }
+}
+
+// Consumes the by-value copy, which is wiped once the key is parsed.
+// The parsed key zeroes itself on drop.
 impl TryFrom<Bip32PrivateKey> for RBip32PrivateKey {
   type Error = CError;
 
-  fn try_from(pk: Bip32PrivateKey) -> Result<Self> {
-    Self::from_bytes(&pk.0).map_err(|e| e.into())
+  fn try_from(mut pk: Bip32PrivateKey) -> Result<Self> {
+    let result = Self::from_bytes(&pk.0).map_err(|e| e.into());
+    pk.zeroize();
+    result
   }
 }
 
 impl From<RBip32PrivateKey> for Bip32PrivateKey {
   fn from(pk: RBip32PrivateKey) -> Self {
-    Self(pk.as_bytes().try_into().unwrap())
+    Self(Zeroizing::new(pk.as_bytes())[..].try_into().unwrap())
   }
 }
 
@@ -81,6 +97,7 @@ pub unsafe extern "C" fn cardano_bip32_private_key_from_bytes(
   }).response(result, error)
 }
 
+// Copies the secret into `result`, release it with cardano_data_wipe.
 #[no_mangle]
 pub unsafe extern "C" fn cardano_bip32_private_key_as_bytes(
   pk: Bip32PrivateKey, result: &mut CData, error: &mut CError
@@ -103,6 +120,7 @@ pub unsafe extern "C" fn cardano_bip32_private_key_from_bech32(
   }).response(result, error)
 }
 
+// The bech32 string holds the secret and is not wiped by cardano_charptr_free.
 #[no_mangle]
 pub unsafe extern "C" fn cardano_bip32_private_key_to_bech32(
   pk: Bip32PrivateKey, result: &mut CharPtr, error: &mut CError
@@ -137,6 +155,7 @@ pub unsafe extern "C" fn cardano_bip32_private_key_from_128_xprv(
   .response(result, error)
 }
 
+// Copies the secret into `result`, release it with cardano_data_wipe.
 #[no_mangle]
 pub unsafe extern "C" fn cardano_bip32_private_key_to_128_xprv(
   pk: Bip32PrivateKey, result: &mut CData, error: &mut CError,
@@ -160,3 +179,164 @@ pub unsafe extern "C" fn cardano_bip32_private_key_chaincode(
   })
   .response(result, error)
 }
+
+// Ledger (SLIP-10 style): BIP39 seed hashed with HMAC-SHA512 until bit 5 of
+// the last scalar byte is clear, then clamped like the Icarus scheme.
+fn ledger_master_key(seed: &[u8]) -> Result<RBip32PrivateKey> {
+  const SEED_KEY: &[u8] = b"ed25519 seed";
+  let mut digest = Zeroizing::new([0u8; 64]);
+  let mut message = Zeroizing::new(seed.to_vec());
+  loop {
+    let mut hmac = Hmac::new(Sha512::new(), SEED_KEY);
+    hmac.input(&message);
+    hmac.raw_result(&mut digest[..]);
+    if digest[31] & 0b0010_0000 == 0 {
+      break;
+    }
+    message = Zeroizing::new(digest.to_vec());
+  }
+  let mut key = Zeroizing::new([0u8; 96]);
+  key[..64].copy_from_slice(&digest[..]);
+  key[0] &= 0b1111_1000;
+  key[31] &= 0b0111_1111;
+  key[31] |= 0b0100_0000;
+  let mut hmac = Hmac::new(Sha256::new(), SEED_KEY);
+  hmac.input(&[1]);
+  hmac.input(seed);
+  hmac.raw_result(&mut key[64..]);
+  RBip32PrivateKey::from_bytes(&key[..]).into_result()
+}
+
+// Trezor feeds the checksum byte into the Icarus scheme for 24 word mnemonics.
+fn trezor_entropy(entropy: &[u8]) -> Zeroizing<Vec<u8>> {
+  let mut entropy = Zeroizing::new(entropy.to_vec());
+  if entropy.len() == 32 {
+    let mut checksum = [0u8; 32];
+    let mut sha = Sha256::new();
+    sha.input(&entropy);
+    sha.result(&mut checksum);
+    entropy.push(checksum[0]);
+  }
+  entropy
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_bip32_private_key_from_ledger_mnemonic(
+  mnemonic: CharPtr, passphrase: CharPtr,
+  result: &mut Bip32PrivateKey, error: &mut CError
+) -> bool {
+  handle_exception_result(|| {
+    mnemonic.unowned().zip(passphrase.unowned())
+      .and_then(|(mnemonic, passphrase)| {
+        RMnemonic::parse(mnemonic)
+          .map_err(|err| err.to_string().into())
+          .map(|mnemonic| Zeroizing::new(mnemonic.to_seed(passphrase)))
+      })
+      .and_then(|seed| ledger_master_key(&seed[..]))
+      .map(|pk| pk.into())
+  }).response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_bip32_private_key_from_trezor_entropy(
+  entropy: CData, password: CData,
+  result: &mut Bip32PrivateKey, error: &mut CError
+) -> bool {
+  handle_exception_result(|| {
+    entropy.unowned().zip(password.unowned())
+      .map(|(ent, pwd)| RBip32PrivateKey::from_bip39_entropy(&trezor_entropy(ent), pwd))
+      .map(|pk| pk.into())
+  }).response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_bip32_private_key_wipe(pk: &mut Bip32PrivateKey) {
+  pk.zeroize()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn ledger_key(mnemonic: &str, passphrase: &str) -> String {
+    let seed = RMnemonic::parse(mnemonic).unwrap().to_seed(passphrase);
+    hex::encode(ledger_master_key(&seed).unwrap().as_bytes())
+  }
+
+  fn trezor_key(entropy: &[u8]) -> String {
+    hex::encode(RBip32PrivateKey::from_bip39_entropy(&trezor_entropy(entropy), &[]).as_bytes())
+  }
+
+  #[test]
+  fn ledger_master_key_retries_until_bit_5_is_clear() {
+    // The first four HMAC rounds have bit 5 of byte 31 set.
+    assert_eq!(
+      ledger_key(
+        "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+         about",
+        ""
+      ),
+      "402b03cd9c8bed9ba9f9bd6cd9c315ce9fcc59c7c25d37c85a36096617e69d41\
+       8e35cb4a3b737afd007f0688618f21a8831643c0e6c77fc33c06026d2a0fc938\
+       32596435e70647d7d98ef102a32ea40319ca8fb6c851d7346d3bd8f9d1492658"
+    );
+  }
+
+  #[test]
+  fn ledger_master_key_single_round() {
+    assert_eq!(
+      ledger_key(
+        "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo \
+         zoo vote",
+        ""
+      ),
+      "38b84ae96187e9eedc77b123c003367be03740096d5b33bd5a9e30d76a661757\
+       24d5db153e5efae69a6d7090f1eca632739797c3589fb2cc312c371386d20f48\
+       eaba90907c9819f3b8d19e65d5d936138b1e4301eab2ec61f1504da6e152247d"
+    );
+  }
+
+  #[test]
+  fn ledger_master_key_with_passphrase() {
+    assert_eq!(
+      ledger_key(
+        "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+         about",
+        "foo"
+      ),
+      "58398319da6291993f34ee76e6f9ec7fef5e23d4515d857275e4113b2897ed52\
+       bbdcc70acf1ba1eaed65b2c9b7b7c2aae4c1b39f3b9ba1a574630df483b1a7d0\
+       9949be14a26dcfe8134bf880f517056348c096a1e219a77da29708bb28de8cdb"
+    );
+  }
+
+  #[test]
+  fn trezor_24_words_include_checksum_byte() {
+    // abandon x23 art
+    let entropy = [0u8; 32];
+    assert_eq!(
+      trezor_key(&entropy),
+      "60e4d66a4ac3f3abdfbabc56a451fe52b265d574879276859d47f03a964a8d52\
+       46069e680f9290ba8cbcc30194d9687cb63d8def4fd00d1a308a4c318bcb4e74\
+       51b8b2cde121e8cfb436804ce4b9dd181860de0fcc3500517fbcf3e6fe7bdbf1"
+    );
+    let icarus = RBip32PrivateKey::from_bip39_entropy(&entropy, &[]).as_bytes();
+    assert_eq!(
+      hex::encode(icarus),
+      "b07ff3e63c17cd2e0504e4bfd52a98c47abde183ccd0738efc385e764fd91d4b\
+       d7d399eeef3c4df68facb3f11e4a4d45513ea1e2a8018aa35b3c078714cfdced\
+       ccc42249e17984c44cf380b489f62c57f84089e150245bf49c436d0b9709c58f"
+    );
+  }
+
+  #[test]
+  fn trezor_other_lengths_match_icarus() {
+    // abandon x11 about
+    assert_eq!(
+      trezor_key(&[0u8; 16]),
+      "60ce7dbec3616e9fc17e0c32578b3f380337b1b61a1f3cb9651aee30670e6f53\
+       970419a23a2e4e4082d12bf78faa8645dfc882cee2ae7179e2b07fe88098abb2\
+       072310084784c7308182dbbdb1449b2706586f1ff5cbf13d15e9b6e78c15f067"
+    );
+  }
+}
@@ -4,13 +4,14 @@ use crate::bip32_private_key::Bip32PrivateKey;
 use crate::data::CData;
 use crate::ed25519_signature::Ed25519Signature;
 use crate::error::CError;
+use crate::legacy_daedalus_private_key::LegacyDaedalusPrivateKey;
 use crate::panic::*;
 use crate::ptr::*;
 use crate::transaction_hash::TransactionHash;
 use crate::vkey::Vkey;
 use cardano_serialization_lib::{
   crypto::{BootstrapWitness as RBootstrapWitness, BootstrapWitnesses as RBootstrapWitnesses},
-  utils::make_icarus_bootstrap_witness,
+  utils::{make_daedalus_bootstrap_witness, make_icarus_bootstrap_witness},
 };
 use std::convert::{TryFrom, TryInto};
 
@@ -85,6 +86,21 @@ pub unsafe extern "C" fn cardano_bootstrap_witness_make_icarus_bootstrap_witness
   .response(result, error)
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn cardano_bootstrap_witness_make_daedalus_bootstrap_witness(
+  tx_body_hash: TransactionHash, addr: ByronAddress, key: LegacyDaedalusPrivateKey,
+  result: &mut BootstrapWitness, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    addr
+      .try_into()
+      .zip(key.try_into())
+      .map(|(addr, key)| make_daedalus_bootstrap_witness(&tx_body_hash.into(), &addr, &key))
+      .map(|bootstrap_witness| bootstrap_witness.into())
+  })
+  .response(result, error)
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn cardano_bootstrap_witness_clone(
   bootstrap_witness: BootstrapWitness, result: &mut BootstrapWitness, error: &mut CError,
@@ -0,0 +1,507 @@
+use crate::array::CArray;
+use crate::asset_name::{display_name, AssetName};
+use crate::error::CError;
+use crate::multi_asset::PolicyID;
+use crate::option::COption;
+use crate::panic::*;
+use crate::ptr::*;
+use crate::string::{CharPtr, IntoCString};
+use crate::transaction_metadata::AuxiliaryData;
+use cardano_serialization_lib::{
+  crypto::ScriptHash as RScriptHash,
+  metadata::{
+    AuxiliaryData as RAuxiliaryData, GeneralTransactionMetadata as RGeneralTransactionMetadata,
+    MetadataList as RMetadataList, MetadataMap as RMetadataMap,
+    TransactionMetadatum as RTransactionMetadatum, TransactionMetadatumKind,
+  },
+  utils::{from_bignum, to_bignum, Int as RInt},
+  AssetName as RAssetName,
+};
+use std::convert::{TryFrom, TryInto};
+
+pub const NFT_METADATA_LABEL: u64 = 721;
+// Metadata strings are limited to 64 bytes, longer values are split into a list.
+pub(crate) const MAX_TEXT_SIZE: usize = 64;
+
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Cip25Version {
+  // Policy ids and asset names are hex and UTF-8 text keys.
+  V1 = 1,
+  // Policy ids and asset names are raw bytes keys.
+  V2 = 2,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct NftFile {
+  name: COption<CharPtr>,
+  media_type: CharPtr,
+  src: CharPtr,
+}
+
+impl Free for NftFile {
+  unsafe fn free(&mut self) {
+    self.name.free();
+    self.media_type.free();
+    self.src.free();
+  }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct NftAsset {
+  policy_id: PolicyID,
+  asset_name: AssetName,
+  // A single metadata string of at most 64 bytes, CIP-25 only splits image and
+  // description into chunks.
+  name: CharPtr,
+  image: CharPtr,
+  media_type: COption<CharPtr>,
+  description: COption<CharPtr>,
+  files: CArray<NftFile>,
+}
+
+impl Free for NftAsset {
+  unsafe fn free(&mut self) {
+    self.name.free();
+    self.image.free();
+    self.media_type.free();
+    self.description.free();
+    self.files.free();
+  }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct NftMetadata {
+  version: Cip25Version,
+  assets: CArray<NftAsset>,
+}
+
+impl Free for NftMetadata {
+  unsafe fn free(&mut self) {
+    self.assets.free();
+  }
+}
+
+struct File {
+  name: Option<String>,
+  media_type: String,
+  src: String,
+}
+
+struct Asset {
+  policy_id: RScriptHash,
+  asset_name: RAssetName,
+  name: String,
+  image: String,
+  media_type: Option<String>,
+  description: Option<String>,
+  files: Vec<File>,
+}
+
+unsafe fn optional_string(value: COption<CharPtr>) -> Result<Option<String>> {
+  Option::from(value).map(|value: CharPtr| value.unowned().map(|s| s.to_string())).transpose()
+}
+
+fn optional_cstr(value: Option<String>) -> COption<CharPtr> {
+  value.map(|value| value.into_cstr()).into()
+}
+
+impl TryFrom<NftFile> for File {
+  type Error = CError;
+
+  fn try_from(file: NftFile) -> Result<Self> {
+    unsafe {
+      Ok(Self {
+        name: optional_string(file.name)?,
+        media_type: file.media_type.unowned()?.to_string(),
+        src: file.src.unowned()?.to_string(),
+      })
+    }
+  }
+}
+
+impl From<File> for NftFile {
+  fn from(file: File) -> Self {
+    Self {
+      name: optional_cstr(file.name),
+      media_type: file.media_type.into_cstr(),
+      src: file.src.into_cstr(),
+    }
+  }
+}
+
+impl TryFrom<NftAsset> for Asset {
+  type Error = CError;
+
+  fn try_from(asset: NftAsset) -> Result<Self> {
+    unsafe {
+      Ok(Self {
+        policy_id: asset.policy_id.into(),
+        asset_name: asset.asset_name.try_into()?,
+        name: asset.name.unowned()?.to_string(),
+        image: asset.image.unowned()?.to_string(),
+        media_type: optional_string(asset.media_type)?,
+        description: optional_string(asset.description)?,
+        files: asset
+          .files
+          .unowned()?
+          .iter()
+          .map(|&file| file.try_into())
+          .collect::<Result<Vec<File>>>()?,
+      })
+    }
+  }
+}
+
+impl TryFrom<Asset> for NftAsset {
+  type Error = CError;
+
+  fn try_from(asset: Asset) -> Result<Self> {
+    Ok(Self {
+      policy_id: asset.policy_id.try_into()?,
+      asset_name: asset.asset_name.try_into()?,
+      name: asset.name.into_cstr(),
+      image: asset.image.into_cstr(),
+      media_type: optional_cstr(asset.media_type),
+      description: optional_cstr(asset.description),
+      files: asset.files.into(),
+    })
+  }
+}
+
+fn check_media_type(media_type: &str, image: bool, path: &str) -> Result<()> {
+  let valid = match media_type.split_once('/') {
+    Some((kind, subtype)) => !subtype.is_empty() && (!image || kind == "image"),
+    None => false,
+  };
+  if valid {
+    Ok(())
+  } else {
+    Err(format!("{}: invalid media type \"{}\"", path, media_type).into())
+  }
+}
+
+pub(crate) fn text(value: &str, path: &str) -> Result<RTransactionMetadatum> {
+  if value.len() > MAX_TEXT_SIZE {
+    return Err(format!("{}: text is longer than {} bytes", path, MAX_TEXT_SIZE).into());
+  }
+  RTransactionMetadatum::new_text(value.to_string()).into_result()
+}
+
+// Chunks end on character boundaries so each one stays valid UTF-8.
+pub(crate) fn chunked_text(value: &str) -> Result<RTransactionMetadatum> {
+  let mut chunks = Vec::new();
+  let mut rest = value;
+  while rest.len() > MAX_TEXT_SIZE {
+    let mut end = MAX_TEXT_SIZE;
+    while !rest.is_char_boundary(end) {
+      end -= 1;
+    }
+    let (chunk, tail) = rest.split_at(end);
+    chunks.push(chunk);
+    rest = tail;
+  }
+  if chunks.is_empty() {
+    return RTransactionMetadatum::new_text(rest.to_string()).into_result();
+  }
+  chunks.push(rest);
+  let mut list = RMetadataList::new();
+  for chunk in chunks {
+    list.add(&RTransactionMetadatum::new_text(chunk.to_string()).into_result()?);
+  }
+  Ok(RTransactionMetadatum::new_list(&list))
+}
+
+fn insert(map: &mut RMetadataMap, key: &str, value: RTransactionMetadatum) -> Result<()> {
+  map.insert_str(key, &value).into_result().map(|_| ())
+}
+
+fn encode_file(file: &File, path: &str) -> Result<RTransactionMetadatum> {
+  let mut map = RMetadataMap::new();
+  if let Some(name) = &file.name {
+    insert(&mut map, "name", text(name, &format!("{}.name", path))?)?;
+  }
+  check_media_type(&file.media_type, false, &format!("{}.mediaType", path))?;
+  insert(&mut map, "mediaType", text(&file.media_type, &format!("{}.mediaType", path))?)?;
+  insert(&mut map, "src", chunked_text(&file.src)?)?;
+  Ok(RTransactionMetadatum::new_map(&map))
+}
+
+fn encode_asset(asset: &Asset, path: &str) -> Result<RTransactionMetadatum> {
+  let mut map = RMetadataMap::new();
+  insert(&mut map, "name", text(&asset.name, &format!("{}.name", path))?)?;
+  insert(&mut map, "image", chunked_text(&asset.image)?)?;
+  if let Some(media_type) = &asset.media_type {
+    check_media_type(media_type, true, &format!("{}.mediaType", path))?;
+    insert(&mut map, "mediaType", text(media_type, &format!("{}.mediaType", path))?)?;
+  }
+  if let Some(description) = &asset.description {
+    insert(&mut map, "description", chunked_text(description)?)?;
+  }
+  if !asset.files.is_empty() {
+    let mut files = RMetadataList::new();
+    for (index, file) in asset.files.iter().enumerate() {
+      files.add(&encode_file(file, &format!("{}.files[{}]", path, index))?);
+    }
+    insert(&mut map, "files", RTransactionMetadatum::new_list(&files))?;
+  }
+  Ok(RTransactionMetadatum::new_map(&map))
+}
+
+fn policy_key(version: Cip25Version, policy_id: &RScriptHash) -> Result<RTransactionMetadatum> {
+  match version {
+    Cip25Version::V1 => RTransactionMetadatum::new_text(hex::encode(policy_id.to_bytes())),
+    Cip25Version::V2 => RTransactionMetadatum::new_bytes(policy_id.to_bytes()),
+  }
+  .into_result()
+}
+
+fn asset_key(
+  version: Cip25Version, asset_name: &RAssetName, path: &str,
+) -> Result<RTransactionMetadatum> {
+  match version {
+    Cip25Version::V1 => String::from_utf8(asset_name.name())
+      .map_err(|_| format!("{}: asset name is not UTF-8, use CIP-25 version 2", path).into())
+      .and_then(|name| RTransactionMetadatum::new_text(name).into_result()),
+    Cip25Version::V2 => RTransactionMetadatum::new_bytes(asset_name.name()).into_result(),
+  }
+}
+
+fn encode(version: Cip25Version, assets: &[Asset]) -> Result<RAuxiliaryData> {
+  // Policies keep the order in which they first appear.
+  let mut policies: Vec<(RTransactionMetadatum, RMetadataMap)> = Vec::new();
+  for asset in assets {
+    let policy_hex = hex::encode(asset.policy_id.to_bytes());
+    let name = display_name(&asset.asset_name.name());
+    let path = format!("{}.{}.{}", NFT_METADATA_LABEL, policy_hex, name);
+    let policy = policy_key(version, &asset.policy_id)?;
+    let key = asset_key(version, &asset.asset_name, &path)?;
+    let index = match policies.iter().position(|(existing, _)| *existing == policy) {
+      Some(index) => index,
+      None => {
+        policies.push((policy, RMetadataMap::new()));
+        policies.len() - 1
+      }
+    };
+    let assets = &mut policies[index].1;
+    if assets.has(&key) {
+      return Err(format!("{}: duplicate asset", path).into());
+    }
+    assets.insert(&key, &encode_asset(asset, &path)?);
+  }
+  let mut root = RMetadataMap::new();
+  for (policy, assets) in policies {
+    root.insert(&policy, &RTransactionMetadatum::new_map(&assets));
+  }
+  if version == Cip25Version::V2 {
+    insert(&mut root, "version", RTransactionMetadatum::new_int(&RInt::new_i32(2)))?;
+  }
+  let mut metadata = RGeneralTransactionMetadata::new();
+  metadata.insert(&to_bignum(NFT_METADATA_LABEL), &RTransactionMetadatum::new_map(&root));
+  let mut auxiliary_data = RAuxiliaryData::new();
+  auxiliary_data.set_metadata(&metadata);
+  Ok(auxiliary_data)
+}
+
+pub(crate) fn as_map(value: &RTransactionMetadatum, path: &str) -> Result<RMetadataMap> {
+  value.as_map().map_err(|_| format!("{}: expected a map", path).into())
+}
+
+pub(crate) fn as_text(value: &RTransactionMetadatum, path: &str) -> Result<String> {
+  value.as_text().map_err(|_| format!("{}: expected text", path).into())
+}
+
+// A single text or a list of text chunks.
+pub(crate) fn as_chunked_text(value: &RTransactionMetadatum, path: &str) -> Result<String> {
+  match value.kind() {
+    TransactionMetadatumKind::Text => as_text(value, path),
+    TransactionMetadatumKind::MetadataList => {
+      let list = value.as_list().into_result()?;
+      (0..list.len())
+        .map(|index| as_text(&list.get(index), &format!("{}[{}]", path, index)))
+        .collect::<Result<Vec<String>>>()
+        .map(|chunks| chunks.concat())
+    }
+    _ => Err(format!("{}: expected text or a list of text", path).into()),
+  }
+}
+
+fn field(map: &RMetadataMap, key: &str) -> Option<RTransactionMetadatum> {
+  map.get_str(key).ok()
+}
+
+fn required(map: &RMetadataMap, key: &str, path: &str) -> Result<RTransactionMetadatum> {
+  field(map, key).ok_or_else(|| format!("{}.{}: missing required field", path, key).into())
+}
+
+fn decode_file(value: &RTransactionMetadatum, path: &str) -> Result<File> {
+  let map = as_map(value, path)?;
+  let name = field(&map, "name")
+    .map(|name| as_text(&name, &format!("{}.name", path)))
+    .transpose()?;
+  let media_type = as_text(&required(&map, "mediaType", path)?, &format!("{}.mediaType", path))?;
+  check_media_type(&media_type, false, &format!("{}.mediaType", path))?;
+  let src = as_chunked_text(&required(&map, "src", path)?, &format!("{}.src", path))?;
+  Ok(File { name, media_type, src })
+}
+
+fn decode_asset(
+  policy_id: &RScriptHash, asset_name: RAssetName, value: &RTransactionMetadatum, path: &str,
+) -> Result<Asset> {
+  let map = as_map(value, path)?;
+  let name = as_text(&required(&map, "name", path)?, &format!("{}.name", path))?;
+  let image = as_chunked_text(&required(&map, "image", path)?, &format!("{}.image", path))?;
+  let media_type = field(&map, "mediaType")
+    .map(|media_type| as_text(&media_type, &format!("{}.mediaType", path)))
+    .transpose()?;
+  if let Some(media_type) = &media_type {
+    check_media_type(media_type, true, &format!("{}.mediaType", path))?;
+  }
+  let description = field(&map, "description")
+    .map(|description| as_chunked_text(&description, &format!("{}.description", path)))
+    .transpose()?;
+  let files = match field(&map, "files") {
+    Some(files) => {
+      let files = files.as_list().map_err(|_| format!("{}.files: expected a list", path))?;
+      (0..files.len())
+        .map(|index| decode_file(&files.get(index), &format!("{}.files[{}]", path, index)))
+        .collect::<Result<Vec<File>>>()?
+    }
+    None => Vec::new(),
+  };
+  Ok(Asset {
+    policy_id: policy_id.clone(),
+    asset_name,
+    name,
+    image,
+    media_type,
+    description,
+    files,
+  })
+}
+
+fn decode_version(root: &RMetadataMap) -> Result<Cip25Version> {
+  let path = format!("{}.version", NFT_METADATA_LABEL);
+  let version = match field(root, "version") {
+    None => return Ok(Cip25Version::V1),
+    Some(version) => match version.kind() {
+      TransactionMetadatumKind::Int => version
+        .as_int()
+        .into_result()?
+        .as_positive()
+        .map(|version| from_bignum(&version).to_string()),
+      TransactionMetadatumKind::Text => version.as_text().ok(),
+      _ => None,
+    },
+  };
+  match version.as_deref() {
+    Some("1") | Some("1.0") => Ok(Cip25Version::V1),
+    Some("2") | Some("2.0") => Ok(Cip25Version::V2),
+    _ => Err(format!("{}: unsupported version", path).into()),
+  }
+}
+
+// Text keys as they are, any other key as hex of its bytes or CBOR.
+fn display_key(key: &RTransactionMetadatum) -> String {
+  match key.kind() {
+    TransactionMetadatumKind::Text => key.as_text().unwrap_or_default(),
+    TransactionMetadatumKind::Bytes => key.as_bytes().map(hex::encode).unwrap_or_default(),
+    _ => hex::encode(key.to_bytes()),
+  }
+}
+
+fn decode_policy(version: Cip25Version, key: &RTransactionMetadatum) -> Result<RScriptHash> {
+  let bytes = match version {
+    Cip25Version::V1 => key.as_text().ok().and_then(|key| hex::decode(key).ok()),
+    Cip25Version::V2 => key.as_bytes().ok(),
+  };
+  bytes.and_then(|bytes| RScriptHash::from_bytes(bytes).ok()).ok_or_else(|| {
+    format!("{}.{}: invalid policy id key", NFT_METADATA_LABEL, display_key(key)).into()
+  })
+}
+
+fn decode_asset_name(
+  version: Cip25Version, key: &RTransactionMetadatum, path: &str,
+) -> Result<RAssetName> {
+  let bytes = match version {
+    Cip25Version::V1 => key.as_text().ok().map(String::into_bytes),
+    Cip25Version::V2 => key.as_bytes().ok(),
+  };
+  bytes
+    .and_then(|bytes| RAssetName::new(bytes).ok())
+    .ok_or_else(|| format!("{}.{}: invalid asset name key", path, display_key(key)).into())
+}
+
+fn decode(auxiliary_data: &RAuxiliaryData) -> Result<(Cip25Version, Vec<Asset>)> {
+  let root = auxiliary_data
+    .metadata()
+    .and_then(|metadata| metadata.get(&to_bignum(NFT_METADATA_LABEL)))
+    .ok_or_else(|| format!("No CIP-25 metadata under label {}", NFT_METADATA_LABEL))?;
+  let root = as_map(&root, &NFT_METADATA_LABEL.to_string())?;
+  let version = decode_version(&root)?;
+  let version_key = RTransactionMetadatum::new_text("version".to_string()).into_result()?;
+  let policies = root.keys();
+  let mut assets = Vec::new();
+  for index in 0..policies.len() {
+    let key = policies.get(index);
+    if key == version_key {
+      continue;
+    }
+    let policy_id = decode_policy(version, &key)?;
+    let path = format!("{}.{}", NFT_METADATA_LABEL, hex::encode(policy_id.to_bytes()));
+    let policy = as_map(&root.get(&key).into_result()?, &path)?;
+    let names = policy.keys();
+    for index in 0..names.len() {
+      let key = names.get(index);
+      let asset_name = decode_asset_name(version, &key, &path)?;
+      let path = format!("{}.{}", path, display_name(&asset_name.name()));
+      let value = policy.get(&key).into_result()?;
+      assets.push(decode_asset(&policy_id, asset_name, &value, &path)?);
+    }
+  }
+  Ok((version, assets))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_nft_metadata_to_auxiliary_data(
+  metadata: NftMetadata, result: &mut AuxiliaryData, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    metadata
+      .assets
+      .unowned()
+      .and_then(|assets| {
+        assets.iter().map(|&asset| asset.try_into()).collect::<Result<Vec<Asset>>>()
+      })
+      .and_then(|assets| encode(metadata.version, &assets))
+      .and_then(|auxiliary_data| auxiliary_data.try_into())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_nft_metadata_from_auxiliary_data(
+  auxiliary_data: AuxiliaryData, result: &mut NftMetadata, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    auxiliary_data
+      .try_into()
+      .and_then(|auxiliary_data: RAuxiliaryData| decode(&auxiliary_data))
+      .and_then(|(version, assets)| {
+        assets
+          .into_iter()
+          .map(|asset| asset.try_into())
+          .collect::<Result<Vec<NftAsset>>>()
+          .map(|assets| NftMetadata { version, assets: assets.into() })
+      })
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_nft_metadata_free(metadata: &mut NftMetadata) {
+  metadata.free()
+}
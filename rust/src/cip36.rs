@@ -0,0 +1,411 @@
+use crate::address::address::Address;
+use crate::array::CArray;
+use crate::cip25::as_map;
+use crate::data::CData;
+use crate::ed25519_signature::Ed25519Signature;
+use crate::error::CError;
+use crate::hash::blake2b;
+use crate::panic::*;
+use crate::private_key::PrivateKey;
+use crate::ptr::*;
+use crate::public_key::PublicKey;
+use crate::transaction_metadata::AuxiliaryData;
+use cardano_serialization_lib::{
+  address::{Address as RAddress, RewardAddress as RRewardAddress},
+  crypto::{
+    Ed25519Signature as REd25519Signature, PrivateKey as RPrivateKey, PublicKey as RPublicKey,
+  },
+  metadata::{
+    AuxiliaryData as RAuxiliaryData, GeneralTransactionMetadata as RGeneralTransactionMetadata,
+    MetadataList as RMetadataList, MetadataMap as RMetadataMap,
+    TransactionMetadatum as RTransactionMetadatum, TransactionMetadatumKind,
+  },
+  utils::{from_bignum, to_bignum, Int as RInt},
+};
+use cbor_event::de::Deserializer;
+use cbor_event::se::Serializer;
+use cbor_event::{Len, Type, Value};
+use std::convert::{TryFrom, TryInto};
+use std::io::Cursor;
+
+pub const REGISTRATION_LABEL: u64 = 61284;
+pub const SIGNATURE_LABEL: u64 = 61285;
+
+const DELEGATIONS_KEY: i32 = 1;
+const STAKE_KEY_KEY: i32 = 2;
+const ADDRESS_KEY: i32 = 3;
+const NONCE_KEY: i32 = 4;
+const VOTING_PURPOSE_KEY: i32 = 5;
+const SIGNATURE_KEY: i32 = 1;
+const AUXILIARY_DATA_METADATA_KEY: u64 = 0;
+
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum VotingRegistrationFormat {
+  // A single voting key and a reward address.
+  Cip15,
+  // Weighted delegations and a voting purpose.
+  Cip36,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct VotingDelegation {
+  voting_key: PublicKey,
+  weight: u32,
+}
+
+impl Free for VotingDelegation {
+  unsafe fn free(&mut self) {}
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct VotingRegistration {
+  format: VotingRegistrationFormat,
+  // CIP-15 registrations hold exactly one delegation, its weight is ignored.
+  delegations: CArray<VotingDelegation>,
+  stake_key: PublicKey,
+  reward_address: Address,
+  nonce: u64,
+  voting_purpose: u64,
+}
+
+impl Free for VotingRegistration {
+  unsafe fn free(&mut self) {
+    self.delegations.free();
+    self.reward_address.free();
+  }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct SignedVotingRegistration {
+  registration: VotingRegistration,
+  signature: Ed25519Signature,
+  valid: bool,
+}
+
+impl Free for SignedVotingRegistration {
+  unsafe fn free(&mut self) {
+    self.registration.free();
+  }
+}
+
+fn uint(value: u64) -> RTransactionMetadatum {
+  RTransactionMetadatum::new_int(&RInt::new(&to_bignum(value)))
+}
+
+fn bytes(value: Vec<u8>) -> Result<RTransactionMetadatum> {
+  RTransactionMetadatum::new_bytes(value).into_result()
+}
+
+fn encode_registration(registration: &VotingRegistration) -> Result<RTransactionMetadatum> {
+  let delegations = unsafe { registration.delegations.unowned()? };
+  let voting_keys = match registration.format {
+    VotingRegistrationFormat::Cip15 => match delegations {
+      [delegation] => {
+        let key: RPublicKey = delegation.voting_key.try_into()?;
+        bytes(key.as_bytes())?
+      }
+      _ => return Err("CIP-15 registrations take exactly one voting key".into()),
+    },
+    VotingRegistrationFormat::Cip36 => {
+      if delegations.is_empty() {
+        return Err("CIP-36 registrations need at least one delegation".into());
+      }
+      let mut list = RMetadataList::new();
+      for delegation in delegations {
+        let key: RPublicKey = delegation.voting_key.try_into()?;
+        let mut pair = RMetadataList::new();
+        pair.add(&bytes(key.as_bytes())?);
+        pair.add(&uint(delegation.weight.into()));
+        list.add(&RTransactionMetadatum::new_list(&pair));
+      }
+      RTransactionMetadatum::new_list(&list)
+    }
+  };
+  let address: RAddress = registration.reward_address.try_into()?;
+  if registration.format == VotingRegistrationFormat::Cip15
+    && RRewardAddress::from_address(&address).is_none()
+  {
+    return Err("CIP-15 registrations need a reward address".into());
+  }
+  let stake_key: RPublicKey = registration.stake_key.try_into()?;
+  let mut map = RMetadataMap::new();
+  map.insert_i32(DELEGATIONS_KEY, &voting_keys);
+  map.insert_i32(STAKE_KEY_KEY, &bytes(stake_key.as_bytes())?);
+  map.insert_i32(ADDRESS_KEY, &bytes(address.to_bytes())?);
+  map.insert_i32(NONCE_KEY, &uint(registration.nonce));
+  if registration.format == VotingRegistrationFormat::Cip36 {
+    map.insert_i32(VOTING_PURPOSE_KEY, &uint(registration.voting_purpose));
+  }
+  Ok(RTransactionMetadatum::new_map(&map))
+}
+
+// The signature covers blake2b-256 of the CBOR map { 61284: registration },
+// with the registration encoded as it was signed.
+fn registration_hash(registration: &[u8]) -> Result<Vec<u8>> {
+  let mut serializer = Serializer::new_vec();
+  serializer
+    .write_map(Len::Len(1))?
+    .write_unsigned_integer(REGISTRATION_LABEL)?
+    .write_raw_bytes(registration)?;
+  Ok(blake2b::<256>(&serializer.finalize()))
+}
+
+fn has_entry(raw: &mut Deserializer<Cursor<&[u8]>>, len: Len, index: u64) -> Result<bool> {
+  match len {
+    Len::Len(len) => Ok(index < len),
+    Len::Indefinite if raw.cbor_type()? == Type::Special => {
+      raw.special()?;
+      Ok(false)
+    }
+    Len::Indefinite => Ok(true),
+  }
+}
+
+// The registration bytes under label 61284 in a Shelley metadata map, a
+// Shelley-MA [metadata, scripts] array or an Alonzo tag 259 map.
+fn raw_registration(auxiliary_data: &[u8]) -> Result<Vec<u8>> {
+  let mut raw = Deserializer::from(Cursor::new(auxiliary_data));
+  match raw.cbor_type()? {
+    Type::Array => {
+      raw.array()?;
+    }
+    Type::Tag => {
+      raw.tag()?;
+      let len = raw.map()?;
+      let mut index = 0;
+      loop {
+        if !has_entry(&mut raw, len, index)? {
+          return Err("Auxiliary data has no metadata".into());
+        }
+        if raw.unsigned_integer()? == AUXILIARY_DATA_METADATA_KEY {
+          break;
+        }
+        raw.deserialize::<Value>()?;
+        index += 1;
+      }
+    }
+    _ => {}
+  }
+  let len = raw.map()?;
+  let mut index = 0;
+  while has_entry(&mut raw, len, index)? {
+    let label = raw.unsigned_integer()?;
+    let start = raw.as_ref().position() as usize;
+    raw.deserialize::<Value>()?;
+    if label == REGISTRATION_LABEL {
+      return Ok(auxiliary_data[start..raw.as_ref().position() as usize].to_vec());
+    }
+    index += 1;
+  }
+  Err(format!("No voting registration under label {}", REGISTRATION_LABEL).into())
+}
+
+fn to_auxiliary_data(
+  registration: &RTransactionMetadatum, signature: &REd25519Signature,
+) -> Result<RAuxiliaryData> {
+  let mut witness = RMetadataMap::new();
+  witness.insert_i32(SIGNATURE_KEY, &bytes(signature.to_bytes())?);
+  let mut metadata = RGeneralTransactionMetadata::new();
+  metadata.insert(&to_bignum(REGISTRATION_LABEL), registration);
+  metadata.insert(&to_bignum(SIGNATURE_LABEL), &RTransactionMetadatum::new_map(&witness));
+  let mut auxiliary_data = RAuxiliaryData::new();
+  auxiliary_data.set_metadata(&metadata);
+  Ok(auxiliary_data)
+}
+
+fn field(map: &RMetadataMap, key: i32, path: &str) -> Result<RTransactionMetadatum> {
+  map
+    .get_i32(key)
+    .map_err(|_| format!("{}.{}: missing required field", path, key).into())
+}
+
+fn as_bytes(value: &RTransactionMetadatum, path: &str) -> Result<Vec<u8>> {
+  value.as_bytes().map_err(|_| format!("{}: expected bytes", path).into())
+}
+
+fn as_uint(value: &RTransactionMetadatum, path: &str) -> Result<u64> {
+  value
+    .as_int()
+    .ok()
+    .and_then(|int| int.as_positive())
+    .map(|int| from_bignum(&int))
+    .ok_or_else(|| format!("{}: expected an unsigned integer", path).into())
+}
+
+fn as_public_key(value: &RTransactionMetadatum, path: &str) -> Result<PublicKey> {
+  as_bytes(value, path)
+    .and_then(|key| {
+      RPublicKey::from_bytes(&key).map_err(|_| format!("{}: invalid public key", path).into())
+    })
+    .map(|key| key.into())
+}
+
+fn decode_delegations(value: &RTransactionMetadatum, path: &str) -> Result<Vec<VotingDelegation>> {
+  match value.kind() {
+    TransactionMetadatumKind::Bytes => {
+      let voting_key = as_public_key(value, path)?;
+      Ok(vec![VotingDelegation { voting_key, weight: 1 }])
+    }
+    TransactionMetadatumKind::MetadataList => {
+      let list = value.as_list().into_result()?;
+      let delegations = (0..list.len())
+        .map(|index| {
+          let path = format!("{}[{}]", path, index);
+          let pair = list.get(index).as_list().ok().filter(|pair| pair.len() == 2);
+          let pair = pair.ok_or_else(|| format!("{}: expected [voting key, weight]", path))?;
+          let weight = as_uint(&pair.get(1), &format!("{}[1]", path))?;
+          Ok(VotingDelegation {
+            voting_key: as_public_key(&pair.get(0), &format!("{}[0]", path))?,
+            weight: u32::try_from(weight)
+              .map_err(|_| format!("{}[1]: weight is too large", path))?,
+          })
+        })
+        .collect::<Result<Vec<VotingDelegation>>>()?;
+      if delegations.is_empty() {
+        return Err(format!("{}: no delegations", path).into());
+      }
+      Ok(delegations)
+    }
+    _ => Err(format!("{}: expected a voting key or a list of delegations", path).into()),
+  }
+}
+
+fn decode(bytes: &[u8]) -> Result<SignedVotingRegistration> {
+  let auxiliary_data = RAuxiliaryData::from_bytes(bytes.to_vec()).into_result()?;
+  let metadata = auxiliary_data.metadata().ok_or("Auxiliary data has no metadata")?;
+  let registration = metadata
+    .get(&to_bignum(REGISTRATION_LABEL))
+    .ok_or_else(|| format!("No voting registration under label {}", REGISTRATION_LABEL))?;
+  let witness = metadata
+    .get(&to_bignum(SIGNATURE_LABEL))
+    .ok_or_else(|| format!("No registration signature under label {}", SIGNATURE_LABEL))?;
+
+  let path = REGISTRATION_LABEL.to_string();
+  let map = as_map(&registration, &path)?;
+  let field_path = |key: i32| format!("{}.{}", path, key);
+  let voting_keys = field(&map, DELEGATIONS_KEY, &path)?;
+  let delegations = decode_delegations(&voting_keys, &field_path(DELEGATIONS_KEY))?;
+  let stake_key = as_public_key(&field(&map, STAKE_KEY_KEY, &path)?, &field_path(STAKE_KEY_KEY))?;
+  let address = as_bytes(&field(&map, ADDRESS_KEY, &path)?, &field_path(ADDRESS_KEY))
+    .and_then(|address| {
+      RAddress::from_bytes(address)
+        .map_err(|_| format!("{}: invalid address", field_path(ADDRESS_KEY)).into())
+    })?;
+  let nonce = as_uint(&field(&map, NONCE_KEY, &path)?, &field_path(NONCE_KEY))?;
+  let voting_purpose = map
+    .get_i32(VOTING_PURPOSE_KEY)
+    .ok()
+    .map(|purpose| as_uint(&purpose, &field_path(VOTING_PURPOSE_KEY)))
+    .transpose()?;
+  // The voting purpose marks CIP-36, which may still use a single voting key.
+  // CIP-15 has no delegation lists, so a list without a purpose is CIP-36 too.
+  let format = match (voting_purpose, voting_keys.kind()) {
+    (None, TransactionMetadatumKind::Bytes) => VotingRegistrationFormat::Cip15,
+    _ => VotingRegistrationFormat::Cip36,
+  };
+
+  let path = SIGNATURE_LABEL.to_string();
+  let signature = as_bytes(&field(&as_map(&witness, &path)?, SIGNATURE_KEY, &path)?, &path)
+    .and_then(|signature| {
+      REd25519Signature::from_bytes(signature)
+        .map_err(|_| format!("{}.{}: invalid signature", path, SIGNATURE_KEY).into())
+    })?;
+  let stake_public_key: RPublicKey = stake_key.try_into()?;
+  let valid =
+    stake_public_key.verify(&registration_hash(&raw_registration(bytes)?)?, &signature);
+
+  Ok(SignedVotingRegistration {
+    registration: VotingRegistration {
+      format,
+      delegations: delegations.into(),
+      stake_key,
+      reward_address: address.try_into()?,
+      nonce,
+      voting_purpose: voting_purpose.unwrap_or(0),
+    },
+    signature: signature.into(),
+    valid,
+  })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_voting_registration_hash(
+  registration: VotingRegistration, result: &mut CData, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    encode_registration(&registration)
+      .and_then(|encoded| registration_hash(&encoded.to_bytes()))
+      .map(|hash| hash.into())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_voting_registration_sign(
+  registration: VotingRegistration, stake_private_key: PrivateKey, result: &mut AuxiliaryData,
+  error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    let key: RPrivateKey = stake_private_key.try_into()?;
+    let stake_key: RPublicKey = registration.stake_key.try_into()?;
+    if key.to_public().as_bytes() != stake_key.as_bytes() {
+      return Err("Stake key does not match the signing key".into());
+    }
+    let encoded = encode_registration(&registration)?;
+    let signature = key.sign(&registration_hash(&encoded.to_bytes())?);
+    to_auxiliary_data(&encoded, &signature).and_then(|auxiliary_data| auxiliary_data.try_into())
+  })
+  .response(result, error)
+}
+
+// For signatures made elsewhere over cardano_voting_registration_hash, e.g. by a hardware wallet.
+#[no_mangle]
+pub unsafe extern "C" fn cardano_voting_registration_with_signature(
+  registration: VotingRegistration, signature: Ed25519Signature, result: &mut AuxiliaryData,
+  error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    let signature: REd25519Signature = signature.try_into()?;
+    let stake_key: RPublicKey = registration.stake_key.try_into()?;
+    let encoded = encode_registration(&registration)?;
+    if !stake_key.verify(&registration_hash(&encoded.to_bytes())?, &signature) {
+      return Err("Signature does not match the registration".into());
+    }
+    to_auxiliary_data(&encoded, &signature).and_then(|auxiliary_data| auxiliary_data.try_into())
+  })
+  .response(result, error)
+}
+
+// Takes the auxiliary data CBOR as it appears on chain, the signature is
+// checked against its original encoding.
+#[no_mangle]
+pub unsafe extern "C" fn cardano_voting_registration_from_auxiliary_data_bytes(
+  auxiliary_data: CData, result: &mut SignedVotingRegistration, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| auxiliary_data.unowned().and_then(decode)).response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_voting_registration_verify_auxiliary_data_bytes(
+  auxiliary_data: CData, result: &mut bool, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    auxiliary_data.unowned().and_then(decode).map(|mut registration| {
+      let valid = registration.valid;
+      registration.free();
+      valid
+    })
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_signed_voting_registration_free(
+  registration: &mut SignedVotingRegistration,
+) {
+  registration.free()
+}
@@ -0,0 +1,71 @@
+use crate::panic::*;
+use bech32::{FromBase32, ToBase32};
+
+pub const ROOT_XSK: &str = "root_xsk";
+pub const ACCT_XSK: &str = "acct_xsk";
+pub const ACCT_XVK: &str = "acct_xvk";
+pub const ADDR_VKH: &str = "addr_vkh";
+pub const STAKE_VKH: &str = "stake_vkh";
+pub const SCRIPT: &str = "script";
+pub const ADDR_VK: &str = "addr_vk";
+pub const STAKE_VK: &str = "stake_vk";
+pub const ADDR_SHARED_VKH: &str = "addr_shared_vkh";
+pub const STAKE_SHARED_VKH: &str = "stake_shared_vkh";
+pub const POLICY_VKH: &str = "policy_vkh";
+pub const POOL: &str = "pool";
+pub const VRF_VKH: &str = "vrf_vkh";
+pub const DATUM: &str = "datum";
+pub const ASSET: &str = "asset";
+
+// Roles an Ed25519 key hash can be shown under.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub enum KeyHashPrefix {
+  AddrVkh,
+  StakeVkh,
+  AddrSharedVkh,
+  StakeSharedVkh,
+  PolicyVkh,
+  Pool,
+}
+
+impl KeyHashPrefix {
+  pub(crate) fn hrp(self) -> &'static str {
+    match self {
+      Self::AddrVkh => ADDR_VKH,
+      Self::StakeVkh => STAKE_VKH,
+      Self::AddrSharedVkh => ADDR_SHARED_VKH,
+      Self::StakeSharedVkh => STAKE_SHARED_VKH,
+      Self::PolicyVkh => POLICY_VKH,
+      Self::Pool => POOL,
+    }
+  }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub enum PublicKeyPrefix {
+  AddrVk,
+  StakeVk,
+}
+
+impl PublicKeyPrefix {
+  pub(crate) fn hrp(self) -> &'static str {
+    match self {
+      Self::AddrVk => ADDR_VK,
+      Self::StakeVk => STAKE_VK,
+    }
+  }
+}
+
+pub(crate) fn encode(hrp: &str, bytes: &[u8]) -> Result<String> {
+  bech32::encode(hrp, bytes.to_base32()).map_err(|err| err.to_string().into())
+}
+
+pub(crate) fn decode(hrp: &str, string: &str) -> Result<Vec<u8>> {
+  let (prefix, data) = bech32::decode(string).map_err(|err| err.to_string())?;
+  if prefix != hrp {
+    return Err(format!("Invalid bech32 prefix \"{}\", expected \"{}\"", prefix, hrp).into());
+  }
+  Vec::<u8>::from_base32(&data).map_err(|err| err.to_string().into())
+}
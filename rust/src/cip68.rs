@@ -0,0 +1,262 @@
+use crate::array::{CArray, CKeyValue};
+use crate::asset_name::AssetName;
+use crate::data::CData;
+use crate::error::CError;
+use crate::option::COption;
+use crate::panic::*;
+use crate::ptr::*;
+use crate::string::{CharPtr, IntoCString};
+use crate::transaction_witness_set::PlutusData;
+use cardano_serialization_lib::{
+  plutus::{
+    ConstrPlutusData as RConstrPlutusData, PlutusData as RPlutusData, PlutusList as RPlutusList,
+    PlutusMap as RPlutusMap,
+  },
+  utils::{from_bignum, to_bignum, BigInt as RBigInt},
+  AssetName as RAssetName,
+};
+use std::convert::{TryFrom, TryInto};
+
+// CIP-67 token classes used by CIP-68.
+pub const REFERENCE_TOKEN_LABEL: u32 = 100;
+pub const NFT_TOKEN_LABEL: u32 = 222;
+pub const FT_TOKEN_LABEL: u32 = 333;
+pub const RFT_TOKEN_LABEL: u32 = 444;
+
+const LABEL_SIZE: usize = 4;
+const DATUM_CONSTRUCTOR: u64 = 0;
+const DATUM_FIELDS: usize = 3;
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct LabeledAssetName {
+  label: u32,
+  // The asset name without its label prefix.
+  name: AssetName,
+}
+
+impl Free for LabeledAssetName {
+  unsafe fn free(&mut self) {}
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub enum Cip68Value {
+  // UTF-8 bytes in the datum.
+  TextKind(CharPtr),
+  DataKind(PlutusData),
+}
+
+impl Free for Cip68Value {
+  unsafe fn free(&mut self) {
+    match self {
+      Cip68Value::TextKind(text) => text.free(),
+      Cip68Value::DataKind(data) => data.free(),
+    }
+  }
+}
+
+pub type Cip68Field = CKeyValue<CharPtr, Cip68Value>;
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct Cip68Datum {
+  metadata: CArray<Cip68Field>,
+  version: u64,
+  extra: PlutusData,
+}
+
+impl Free for Cip68Datum {
+  unsafe fn free(&mut self) {
+    self.metadata.free();
+    self.extra.free();
+  }
+}
+
+// CRC-8 with polynomial 0x07 and no reflection, as specified by CIP-67.
+fn crc8(bytes: &[u8]) -> u8 {
+  bytes.iter().fold(0u8, |crc, byte| {
+    (0..8).fold(crc ^ byte, |crc, _| {
+      if crc & 0x80 != 0 {
+        (crc << 1) ^ 0x07
+      } else {
+        crc << 1
+      }
+    })
+  })
+}
+
+// 0000 | 16 bit label | 8 bit checksum | 0000
+fn label_prefix(label: u32) -> Result<[u8; LABEL_SIZE]> {
+  let label =
+    u16::try_from(label).map_err(|_| format!("Asset name label {} is out of range", label))?;
+  let [high, low] = label.to_be_bytes();
+  let crc = crc8(&[high, low]);
+  Ok([high >> 4, (high << 4) | (low >> 4), (low << 4) | (crc >> 4), crc << 4])
+}
+
+fn parse_label(name: &[u8]) -> Option<u32> {
+  if name.len() < LABEL_SIZE || name[0] & 0xf0 != 0 || name[3] & 0x0f != 0 {
+    return None;
+  }
+  let high = (name[0] << 4) | (name[1] >> 4);
+  let low = (name[1] << 4) | (name[2] >> 4);
+  let crc = (name[2] << 4) | (name[3] >> 4);
+  Some(u16::from_be_bytes([high, low]))
+    .filter(|label| crc8(&label.to_be_bytes()) == crc)
+    .map(u32::from)
+}
+
+fn labeled_name(label: u32, name: &[u8]) -> Result<RAssetName> {
+  let mut bytes = label_prefix(label)?.to_vec();
+  bytes.extend_from_slice(name);
+  RAssetName::new(bytes).into_result()
+}
+
+fn bytes_data(text: &str) -> RPlutusData {
+  RPlutusData::new_bytes(text.as_bytes().to_vec())
+}
+
+fn encode_datum(datum: Cip68Datum) -> Result<RPlutusData> {
+  if datum.version == 0 {
+    return Err("CIP-68 datum version must be at least 1".into());
+  }
+  let mut metadata = RPlutusMap::new();
+  for field in unsafe { datum.metadata.unowned()? } {
+    let key = unsafe { field.key.unowned()? };
+    let value = match field.val {
+      Cip68Value::TextKind(text) => bytes_data(unsafe { text.unowned()? }),
+      Cip68Value::DataKind(data) => data.try_into()?,
+    };
+    if metadata.insert(&bytes_data(key), &value).is_some() {
+      return Err(format!("Duplicate CIP-68 metadata field \"{}\"", key).into());
+    }
+  }
+  let mut fields = RPlutusList::new();
+  fields.add(&RPlutusData::new_map(&metadata));
+  fields.add(&RPlutusData::new_integer(
+    &RBigInt::from_str(&datum.version.to_string()).into_result()?,
+  ));
+  fields.add(&datum.extra.try_into()?);
+  Ok(RPlutusData::new_constr_plutus_data(&RConstrPlutusData::new(
+    &to_bignum(DATUM_CONSTRUCTOR),
+    &fields,
+  )))
+}
+
+fn decode_value(value: RPlutusData) -> Result<Cip68Value> {
+  match value.as_bytes().map(String::from_utf8) {
+    Some(Ok(text)) => Ok(Cip68Value::TextKind(text.into_cstr())),
+    _ => value.try_into().map(Cip68Value::DataKind),
+  }
+}
+
+fn decode_datum(data: &RPlutusData) -> Result<Cip68Datum> {
+  let constr = data
+    .as_constr_plutus_data()
+    .filter(|constr| from_bignum(&constr.alternative()) == DATUM_CONSTRUCTOR)
+    .ok_or_else(|| format!("CIP-68 datum must use constructor {}", DATUM_CONSTRUCTOR))?;
+  let fields = constr.data();
+  if fields.len() != DATUM_FIELDS {
+    let message = format!("CIP-68 datum has {} fields, expected {}", fields.len(), DATUM_FIELDS);
+    return Err(message.into());
+  }
+  let metadata = fields.get(0).as_map().ok_or("CIP-68 metadata must be a map")?;
+  let keys = metadata.keys();
+  let metadata = (0..keys.len())
+    .map(|index| {
+      let key = keys.get(index);
+      let name = key
+        .as_bytes()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or_else(|| format!("CIP-68 metadata key {} is not UTF-8 bytes", index))?;
+      let value = metadata.get(&key).ok_or("Cannot get PlutusData by key")?;
+      decode_value(value).map(|value| (name.into_cstr(), value).into())
+    })
+    .collect::<Result<Vec<Cip68Field>>>()?;
+  let version = fields
+    .get(1)
+    .as_integer()
+    .and_then(|version| version.as_u64())
+    .map(|version| from_bignum(&version))
+    .filter(|version| *version > 0)
+    .ok_or("CIP-68 version must be a positive integer")?;
+  Ok(Cip68Datum {
+    metadata: metadata.into(),
+    version,
+    extra: fields.get(2).try_into()?,
+  })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_asset_name_from_label(
+  label: u32, name: CData, result: &mut AssetName, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    name
+      .unowned()
+      .and_then(|name| labeled_name(label, name))
+      .and_then(|asset_name| asset_name.try_into())
+  })
+  .response(result, error)
+}
+
+// None when the name has no CIP-67 prefix or its checksum does not match.
+#[no_mangle]
+pub unsafe extern "C" fn cardano_asset_name_label(
+  asset_name: AssetName, result: &mut COption<LabeledAssetName>, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    let name = RAssetName::try_from(asset_name)?.name();
+    parse_label(&name)
+      .map(|label| {
+        RAssetName::new(name[LABEL_SIZE..].to_vec())
+          .into_result()
+          .and_then(|name| name.try_into())
+          .map(|name| LabeledAssetName { label, name })
+      })
+      .transpose()
+      .map(|labeled| labeled.into())
+  })
+  .response(result, error)
+}
+
+// The (100) reference token holding the datum of a CIP-68 user token.
+#[no_mangle]
+pub unsafe extern "C" fn cardano_asset_name_reference_token(
+  asset_name: AssetName, result: &mut AssetName, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    let name = RAssetName::try_from(asset_name)?.name();
+    match parse_label(&name) {
+      Some(REFERENCE_TOKEN_LABEL) => Err("Asset name is already a reference token".into()),
+      Some(_) => labeled_name(REFERENCE_TOKEN_LABEL, &name[LABEL_SIZE..]),
+      None => Err("Asset name has no CIP-67 label".into()),
+    }
+    .and_then(|asset_name| asset_name.try_into())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_cip68_datum_to_plutus_data(
+  datum: Cip68Datum, result: &mut PlutusData, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| encode_datum(datum).and_then(|data| data.try_into()))
+    .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_cip68_datum_from_plutus_data(
+  data: PlutusData, result: &mut Cip68Datum, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    data.try_into().and_then(|data: RPlutusData| decode_datum(&data))
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_cip68_datum_free(datum: &mut Cip68Datum) {
+  datum.free()
+}
@@ -1,6 +1,7 @@
 use super::ptr::{Ptr, Free};
 use super::error::CError;
 use super::panic::Result;
+use zeroize::Zeroize;
 
 #[repr(C)]
 #[derive(Copy, Clone)]
@@ -50,4 +51,13 @@ impl From<Vec<u8>> for CData {
 #[no_mangle]
 pub unsafe extern "C" fn cardano_data_free(data: &mut CData) {
   data.free();
-}
\ No newline at end of file
+}
+
+// Zeroes the buffer before releasing it, for data holding key material.
+#[no_mangle]
+pub unsafe extern "C" fn cardano_data_wipe(data: &mut CData) {
+  if !data.ptr.is_null() {
+    std::slice::from_raw_parts_mut(data.ptr as *mut u8, data.len).zeroize();
+  }
+  data.free();
+}
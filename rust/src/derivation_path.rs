@@ -0,0 +1,265 @@
+use crate::array::CArray;
+use crate::bip32_private_key::Bip32PrivateKey;
+use crate::bip32_public_key::Bip32PublicKey;
+use crate::error::CError;
+use crate::panic::*;
+use crate::ptr::*;
+use crate::stake_credential::Ed25519KeyHash;
+use crate::string::{CharPtr, IntoCString};
+use cardano_serialization_lib::crypto::{
+  Bip32PrivateKey as RBip32PrivateKey, Bip32PublicKey as RBip32PublicKey,
+};
+use std::convert::{TryFrom, TryInto};
+
+pub const HARDENED_INDEX: u32 = 0x80000000;
+pub const PURPOSE_CIP1852: u32 = 1852;
+pub const PURPOSE_CIP1854: u32 = 1854;
+pub const PURPOSE_CIP1855: u32 = 1855;
+pub const COIN_TYPE_ADA: u32 = 1815;
+
+pub type DerivationPath = CArray<u32>;
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub enum KeyRole {
+  External = 0,
+  Internal = 1,
+  Staking = 2,
+}
+
+pub(crate) fn harden(index: u32) -> Result<u32> {
+  if index >= HARDENED_INDEX {
+    Err(format!("Index {} is out of range for hardened derivation", index).into())
+  } else {
+    Ok(index | HARDENED_INDEX)
+  }
+}
+
+fn soft(index: u32) -> Result<u32> {
+  if index >= HARDENED_INDEX {
+    Err(format!("Index {} is out of range for soft derivation", index).into())
+  } else {
+    Ok(index)
+  }
+}
+
+// m / purpose' / coin_type' / account' / role / index
+pub(crate) fn account_path(purpose: u32, account: u32) -> Result<Vec<u32>> {
+  Ok(vec![harden(purpose)?, harden(COIN_TYPE_ADA)?, harden(account)?])
+}
+
+pub(crate) fn cip1852_path(account: u32, role: KeyRole, index: u32) -> Result<Vec<u32>> {
+  let mut path = account_path(PURPOSE_CIP1852, account)?;
+  path.push(role as u32);
+  path.push(soft(index)?);
+  Ok(path)
+}
+
+pub(crate) fn cip1854_path(account: u32, role: KeyRole, index: u32) -> Result<Vec<u32>> {
+  let mut path = account_path(PURPOSE_CIP1854, account)?;
+  match role {
+    KeyRole::External | KeyRole::Staking => path.push(role as u32),
+    KeyRole::Internal => return Err("CIP-1854 has no internal chain".into()),
+  }
+  path.push(soft(index)?);
+  Ok(path)
+}
+
+// m / 1855' / 1815' / policy_ix'
+pub(crate) fn cip1855_path(policy_index: u32) -> Result<Vec<u32>> {
+  Ok(vec![harden(PURPOSE_CIP1855)?, harden(COIN_TYPE_ADA)?, harden(policy_index)?])
+}
+
+// Accepts "m/1852'/1815'/0'/2/0", "H" and "h" mark hardened segments too.
+pub(crate) fn parse_path(path: &str) -> Result<Vec<u32>> {
+  let path = path.trim();
+  let segments = match path.strip_prefix('m') {
+    Some("") => return Ok(Vec::new()),
+    Some(rest) => rest
+      .strip_prefix('/')
+      .ok_or_else(|| CError::from(format!("Invalid derivation path \"{}\"", path)))?,
+    None => path,
+  };
+  segments
+    .split('/')
+    .map(|segment| {
+      let (number, hardened) = match segment.strip_suffix(|c| c == '\'' || c == 'h' || c == 'H') {
+        Some(number) => (number, true),
+        None => (segment, false),
+      };
+      number
+        .parse::<u32>()
+        .ok()
+        .filter(|_| number.chars().all(|c| c.is_ascii_digit()))
+        .ok_or_else(|| CError::from(format!("Invalid derivation path segment \"{}\"", segment)))
+        .and_then(|index| if hardened { harden(index) } else { soft(index) })
+    })
+    .collect()
+}
+
+pub(crate) fn format_path(path: &[u32]) -> String {
+  std::iter::once("m".to_string())
+    .chain(path.iter().map(|index| {
+      if index & HARDENED_INDEX != 0 {
+        format!("{}'", index & !HARDENED_INDEX)
+      } else {
+        index.to_string()
+      }
+    }))
+    .collect::<Vec<String>>()
+    .join("/")
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_derivation_path_from_string(
+  path: CharPtr, result: &mut DerivationPath, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    path
+      .unowned()
+      .and_then(parse_path)
+      .map(|path| path.into())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_derivation_path_to_string(
+  path: DerivationPath, result: &mut CharPtr, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| path.unowned().map(|path| format_path(path).into_cstr()))
+    .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_derivation_path_cip1852(
+  account: u32, role: KeyRole, index: u32, result: &mut DerivationPath, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| cip1852_path(account, role, index).map(|path| path.into()))
+    .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_derivation_path_cip1854(
+  account: u32, role: KeyRole, index: u32, result: &mut DerivationPath, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| cip1854_path(account, role, index).map(|path| path.into()))
+    .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_derivation_path_cip1855(
+  policy_index: u32, result: &mut DerivationPath, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| cip1855_path(policy_index).map(|path| path.into()))
+    .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_derivation_path_free(path: &mut DerivationPath) {
+  path.free()
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct DerivedKey {
+  key: Bip32PrivateKey,
+  hash: Ed25519KeyHash,
+}
+
+impl TryFrom<RBip32PrivateKey> for DerivedKey {
+  type Error = CError;
+
+  fn try_from(key: RBip32PrivateKey) -> Result<Self> {
+    let hash = key.to_raw_key().to_public().hash().try_into()?;
+    Ok(Self { key: key.into(), hash })
+  }
+}
+
+fn derive_private(key: Bip32PrivateKey, path: &[u32]) -> Result<RBip32PrivateKey> {
+  let key: RBip32PrivateKey = key.try_into()?;
+  Ok(path.iter().fold(key, |key, index| key.derive(*index)))
+}
+
+fn derive_key(root: Bip32PrivateKey, path: Result<Vec<u32>>) -> Result<DerivedKey> {
+  path.and_then(|path| derive_private(root, &path)).and_then(|key| key.try_into())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_bip32_private_key_derive_path(
+  key: Bip32PrivateKey, path: DerivationPath, result: &mut Bip32PrivateKey, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    path
+      .unowned()
+      .and_then(|path| derive_private(key, path))
+      .map(|key| key.into())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_bip32_public_key_derive_path(
+  key: Bip32PublicKey, path: DerivationPath, result: &mut Bip32PublicKey, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    path.unowned().zip(key.try_into()).and_then(|(path, key): (_, RBip32PublicKey)| {
+      path
+        .iter()
+        .try_fold(key, |key, index| key.derive(*index).into_result())
+        .map(|key| key.into())
+    })
+  })
+  .response(result, error)
+}
+
+// m / 1852' / 1815' / account'
+#[no_mangle]
+pub unsafe extern "C" fn cardano_bip32_private_key_cip1852_account(
+  root: Bip32PrivateKey, account: u32, result: &mut DerivedKey, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| derive_key(root, account_path(PURPOSE_CIP1852, account)))
+    .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_bip32_private_key_cip1852_external(
+  root: Bip32PrivateKey, account: u32, index: u32, result: &mut DerivedKey, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| derive_key(root, cip1852_path(account, KeyRole::External, index)))
+    .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_bip32_private_key_cip1852_internal(
+  root: Bip32PrivateKey, account: u32, index: u32, result: &mut DerivedKey, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| derive_key(root, cip1852_path(account, KeyRole::Internal, index)))
+    .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_bip32_private_key_cip1852_staking(
+  root: Bip32PrivateKey, account: u32, index: u32, result: &mut DerivedKey, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| derive_key(root, cip1852_path(account, KeyRole::Staking, index)))
+    .response(result, error)
+}
+
+// Multisig payment (role 0) and stake (role 2) keys.
+#[no_mangle]
+pub unsafe extern "C" fn cardano_bip32_private_key_cip1854_multisig(
+  root: Bip32PrivateKey, account: u32, role: KeyRole, index: u32, result: &mut DerivedKey,
+  error: &mut CError,
+) -> bool {
+  handle_exception_result(|| derive_key(root, cip1854_path(account, role, index)))
+    .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_bip32_private_key_cip1855_minting(
+  root: Bip32PrivateKey, policy_index: u32, result: &mut DerivedKey, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| derive_key(root, cip1855_path(policy_index)))
+    .response(result, error)
+}
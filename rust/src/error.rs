@@ -61,6 +61,12 @@ impl From<serde_json::Error> for CError {
     }
 }
 
+impl From<cbor_event::Error> for CError {
+    fn from(error: cbor_event::Error) -> Self {
+        Self::DeserializeError(format!("{}", error).into_cstr())
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn cardano_error_free(err: &mut CError) {
     err.free();
@@ -11,13 +11,15 @@ use crate::transaction_metadatum_labels::TransactionMetadatumLabel;
 use cardano_serialization_lib::{
   metadata::{
     decode_arbitrary_bytes_from_metadatum, decode_metadatum_to_json_str,
-    encode_arbitrary_bytes_as_metadatum, encode_json_str_to_metadatum,
+    decode_metadatum_to_json_value, encode_arbitrary_bytes_as_metadatum,
+    encode_json_str_to_metadatum, encode_json_value_to_metadatum,
     GeneralTransactionMetadata as RGeneralTransactionMetadata,
     MetadataJsonSchema as RMetadataJsonSchema, TransactionMetadatum as RTransactionMetadatum,
     TransactionMetadatumKind,
   },
   utils::{from_bignum, to_bignum, Int as RInt},
 };
+use serde_json::Value as JsonValue;
 use std::convert::{TryFrom, TryInto};
 
 #[repr(C)]
@@ -299,6 +301,202 @@ impl TryFrom<RGeneralTransactionMetadata> for GeneralTransactionMetadata {
   }
 }
 
+// Nested values of a metadatum JSON with their paths. Map keys share the path of their value.
+fn json_children(
+  value: &JsonValue, schema: RMetadataJsonSchema, path: &str,
+) -> Vec<(String, JsonValue)> {
+  match (schema, value) {
+    (RMetadataJsonSchema::DetailedSchema, JsonValue::Object(object)) if object.len() == 1 => {
+      match object.iter().next() {
+        Some((tag, JsonValue::Array(items))) if tag == "list" => items
+          .iter()
+          .enumerate()
+          .map(|(index, item)| (format!("{}.list[{}]", path, index), item.clone()))
+          .collect(),
+        Some((tag, JsonValue::Array(entries))) if tag == "map" => entries
+          .iter()
+          .enumerate()
+          .flat_map(|(index, entry)| {
+            let path = format!("{}.map[{}]", path, index);
+            match (entry.get("k"), entry.get("v")) {
+              (Some(key), Some(value)) => vec![
+                (format!("{}.k", path), key.clone()),
+                (format!("{}.v", path), value.clone()),
+              ],
+              // Fails to encode as a tagged value, so the error stops here.
+              _ => vec![(path, entry.clone())],
+            }
+          })
+          .collect(),
+        _ => vec![],
+      }
+    }
+    (RMetadataJsonSchema::DetailedSchema, _) => vec![],
+    (_, JsonValue::Array(items)) => items
+      .iter()
+      .enumerate()
+      .map(|(index, item)| (format!("{}[{}]", path, index), item.clone()))
+      .collect(),
+    (_, JsonValue::Object(object)) => object
+      .iter()
+      .flat_map(|(key, value)| {
+        let path = format!("{}.{}", path, key);
+        vec![(path.clone(), JsonValue::String(key.clone())), (path, value.clone())]
+      })
+      .collect(),
+    _ => vec![],
+  }
+}
+
+// The deepest value that fails to encode on its own.
+fn json_error_path(value: &JsonValue, schema: RMetadataJsonSchema, path: String) -> String {
+  json_children(value, schema, &path)
+    .into_iter()
+    .find(|(_, child)| encode_json_value_to_metadatum(child.clone(), schema).is_err())
+    .map(|(path, child)| json_error_path(&child, schema, path))
+    .unwrap_or(path)
+}
+
+fn metadatum_key_path(key: &RTransactionMetadatum, index: usize) -> String {
+  match key.kind() {
+    TransactionMetadatumKind::Text => key.as_text().ok(),
+    TransactionMetadatumKind::Int => key.as_int().ok().map(|int| int.to_str()),
+    TransactionMetadatumKind::Bytes => {
+      key.as_bytes().ok().map(|bytes| format!("0x{}", hex::encode(bytes)))
+    }
+    _ => None,
+  }
+  .unwrap_or_else(|| format!("<key {}>", index))
+}
+
+// Nested metadatums with their paths, map keys are flagged as such.
+fn metadatum_children(
+  metadatum: &RTransactionMetadatum, path: &str,
+) -> Vec<(String, RTransactionMetadatum, bool)> {
+  match metadatum.kind() {
+    TransactionMetadatumKind::MetadataList => metadatum
+      .as_list()
+      .map(|list| {
+        (0..list.len())
+          .map(|index| (format!("{}[{}]", path, index), list.get(index), false))
+          .collect()
+      })
+      .unwrap_or_default(),
+    TransactionMetadatumKind::MetadataMap => metadatum
+      .as_map()
+      .map(|map| {
+        let keys = map.keys();
+        (0..keys.len())
+          .flat_map(|index| {
+            let key = keys.get(index);
+            let path = format!("{}.{}", path, metadatum_key_path(&key, index));
+            let value = map.get(&key).ok().map(|value| (path.clone(), value, false));
+            std::iter::once((path, key, true)).chain(value)
+          })
+          .collect()
+      })
+      .unwrap_or_default(),
+    _ => vec![],
+  }
+}
+
+// JSON object keys must be text, or also ints and bytes with basic conversions.
+fn metadatum_decodes(
+  metadatum: &RTransactionMetadatum, is_key: bool, schema: RMetadataJsonSchema,
+) -> bool {
+  let key_allowed = match (schema, metadatum.kind()) {
+    (RMetadataJsonSchema::DetailedSchema, _) | (_, TransactionMetadatumKind::Text) => true,
+    (RMetadataJsonSchema::BasicConversions, TransactionMetadatumKind::Int)
+    | (RMetadataJsonSchema::BasicConversions, TransactionMetadatumKind::Bytes) => true,
+    _ => !is_key,
+  };
+  key_allowed && decode_metadatum_to_json_value(metadatum, schema).is_ok()
+}
+
+fn metadatum_error_path(
+  metadatum: &RTransactionMetadatum, schema: RMetadataJsonSchema, path: String,
+) -> String {
+  metadatum_children(metadatum, &path)
+    .into_iter()
+    .find(|(_, child, is_key)| !metadatum_decodes(child, *is_key, schema))
+    .map(|(path, child, _)| metadatum_error_path(&child, schema, path))
+    .unwrap_or(path)
+}
+
+// cardano-cli metadata JSON: an object of metadatums keyed by decimal labels.
+pub(crate) fn metadata_from_json(
+  json: &str, schema: MetadataJsonSchema,
+) -> Result<RGeneralTransactionMetadata> {
+  let json: JsonValue = serde_json::from_str(json)?;
+  let object = json.as_object().ok_or("Metadata JSON must be an object keyed by label")?;
+  let schema = schema.into();
+  let mut metadata = RGeneralTransactionMetadata::new();
+  for (label, value) in object {
+    let tm_label = label
+      .parse::<TransactionMetadatumLabel>()
+      .map_err(|_| format!("{}: label must be an unsigned integer", label))?;
+    let transaction_metadatum = encode_json_value_to_metadatum(value.clone(), schema)
+      .map_err(|error| format!("{}: {}", json_error_path(value, schema, label.clone()), error))?;
+    metadata.insert(&to_bignum(tm_label), &transaction_metadatum);
+  }
+  Ok(metadata)
+}
+
+// Labels are written in ascending numeric order, as cardano-cli does.
+pub(crate) fn metadata_to_json(
+  metadata: &RGeneralTransactionMetadata, schema: MetadataJsonSchema,
+) -> Result<String> {
+  let schema = schema.into();
+  let tm_labels = metadata.keys();
+  let mut tm_labels: Vec<TransactionMetadatumLabel> =
+    (0..tm_labels.len()).map(|index| from_bignum(&tm_labels.get(index))).collect();
+  tm_labels.sort_unstable();
+  tm_labels
+    .into_iter()
+    .map(|tm_label| {
+      let tm = metadata
+        .get(&to_bignum(tm_label))
+        .ok_or("Cannot get TransactionMetadatum by TransactionMetadatumLabel")?;
+      decode_metadatum_to_json_value(&tm, schema)
+        .map(|value| format!("\"{}\":{}", tm_label, value))
+        .map_err(|error| {
+          format!("{}: {}", metadatum_error_path(&tm, schema, tm_label.to_string()), error).into()
+        })
+    })
+    .collect::<Result<Vec<String>>>()
+    .map(|entries| format!("{{{}}}", entries.join(",")))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_general_transaction_metadata_from_json(
+  json: CharPtr, schema: MetadataJsonSchema, result: &mut GeneralTransactionMetadata,
+  error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    json
+      .unowned()
+      .and_then(|json| metadata_from_json(json, schema))
+      .and_then(|general_transaction_metadata| general_transaction_metadata.try_into())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_general_transaction_metadata_to_json(
+  general_transaction_metadata: GeneralTransactionMetadata, schema: MetadataJsonSchema,
+  result: &mut CharPtr, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    general_transaction_metadata
+      .try_into()
+      .and_then(|general_transaction_metadata| {
+        metadata_to_json(&general_transaction_metadata, schema)
+      })
+      .map(|json| json.into_cstr())
+  })
+  .response(result, error)
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn cardano_general_transaction_metadata_free(
   general_transaction_metadata: &mut GeneralTransactionMetadata,
@@ -1,7 +1,9 @@
+use crate::cip5;
 use crate::data::CData;
 use crate::error::CError;
 use crate::panic::*;
 use crate::ptr::Ptr;
+use crate::string::{CharPtr, IntoCString};
 use cardano_serialization_lib::{
   crypto::{
     GenesisDelegateHash as RGenesisDelegateHash, GenesisHash as RGenesisHash,
@@ -139,6 +141,31 @@ pub unsafe extern "C" fn cardano_vrf_key_hash_from_bytes(
   .response(result, error)
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn cardano_vrf_key_hash_to_bech32(
+  vrf_key_hash: VRFKeyHash, result: &mut CharPtr, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    let vrf_key_hash: RVRFKeyHash = vrf_key_hash.into();
+    cip5::encode(cip5::VRF_VKH, &vrf_key_hash.to_bytes()).map(|bech32| bech32.into_cstr())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_vrf_key_hash_from_bech32(
+  bech32: CharPtr, result: &mut VRFKeyHash, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    bech32
+      .unowned()
+      .and_then(|bech32| cip5::decode(cip5::VRF_VKH, bech32))
+      .and_then(|bytes| RVRFKeyHash::from_bytes(bytes).into_result())
+      .map(|vrf_key_hash| vrf_key_hash.into())
+  })
+  .response(result, error)
+}
+
 #[repr(C)]
 #[derive(Copy, Clone)]
 pub struct GenesisKeyDelegation {
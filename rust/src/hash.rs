@@ -0,0 +1,8 @@
+use cryptoxide::hashing::blake2b::Context;
+
+// Blake2b with a `BITS` long digest, e.g. 224 for key and script hashes.
+pub(crate) fn blake2b<const BITS: usize>(data: &[u8]) -> Vec<u8> {
+  let mut hash = vec![0u8; BITS / 8];
+  Context::<BITS>::new().update(data).finalize_at(&mut hash);
+  hash
+}
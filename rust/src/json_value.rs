@@ -37,6 +37,12 @@ impl Free for WrappedCharPtr {
   }
 }
 
+impl From<&str> for WrappedCharPtr {
+  fn from(string: &str) -> Self {
+    Self(string.into_cstr())
+  }
+}
+
 pub type JsonValueMapKeyValue = CKeyValue<WrappedCharPtr, JsonValue>;
 
 #[repr(C)]
@@ -0,0 +1,296 @@
+use crate::bip32_private_key::Bip32PrivateKey;
+use crate::data::CData;
+use crate::error::CError;
+use crate::panic::*;
+use crate::ptr::*;
+use cardano_serialization_lib::crypto::Bip32PrivateKey as RBip32PrivateKey;
+use cryptoxide::chacha20poly1305::ChaCha20Poly1305;
+use cryptoxide::hmac::Hmac;
+use cryptoxide::pbkdf2::pbkdf2;
+use cryptoxide::scrypt::{scrypt, ScryptParams};
+use cryptoxide::sha2::Sha512;
+use std::convert::TryInto;
+use zeroize::Zeroizing;
+
+const MAGIC: &[u8; 4] = b"CKS\0";
+const VERSION: u8 = 1;
+
+const SALT_SIZE: usize = 32;
+const NONCE_SIZE: usize = 12;
+const TAG_SIZE: usize = 16;
+const KEY_SIZE: usize = 32;
+const SECRET_SIZE: usize = 96;
+
+// Parameters of the Yoroi / EMIP-3 format.
+const EMIP3_ITERATIONS: u32 = 19_162;
+
+const PBKDF2_ITERATIONS: u32 = 210_000;
+const PBKDF2_MAX_ITERATIONS: u32 = 10_000_000;
+const SCRYPT_LOG_N: u8 = 15;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+const SCRYPT_MAX_LOG_N: u8 = 20;
+const SCRYPT_MAX_R_P: u32 = 64;
+
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum KeystoreFormat {
+  // salt | nonce | tag | ciphertext, as written by Yoroi and
+  // cardano-serialization-lib encrypt_with_password.
+  Emip3 = 0,
+  Pbkdf2 = 1,
+  Scrypt = 2,
+}
+
+#[derive(Clone, Copy)]
+enum Kdf {
+  Pbkdf2 { iterations: u32 },
+  Scrypt { log_n: u8, r: u32, p: u32 },
+}
+
+impl Kdf {
+  fn id(&self) -> u8 {
+    match self {
+      Self::Pbkdf2 { .. } => KeystoreFormat::Pbkdf2 as u8,
+      Self::Scrypt { .. } => KeystoreFormat::Scrypt as u8,
+    }
+  }
+
+  fn derive(&self, password: &[u8], salt: &[u8]) -> Zeroizing<[u8; KEY_SIZE]> {
+    let mut key = Zeroizing::new([0u8; KEY_SIZE]);
+    match *self {
+      Self::Pbkdf2 { iterations } => {
+        let mut mac = Hmac::new(Sha512::new(), password);
+        pbkdf2(&mut mac, salt, iterations, &mut key[..]);
+      }
+      Self::Scrypt { log_n, r, p } => {
+        scrypt(password, salt, &ScryptParams::new(log_n, r, p), &mut key[..]);
+      }
+    }
+    key
+  }
+
+  fn write(&self, out: &mut Vec<u8>) {
+    out.push(self.id());
+    match *self {
+      Self::Pbkdf2 { iterations } => out.extend_from_slice(&iterations.to_be_bytes()),
+      Self::Scrypt { log_n, r, p } => {
+        out.push(log_n);
+        out.extend_from_slice(&r.to_be_bytes());
+        out.extend_from_slice(&p.to_be_bytes());
+      }
+    }
+  }
+
+  // Parameters come from the file, so they are bounded before running the KDF.
+  fn read(data: &[u8]) -> Result<(Self, usize)> {
+    let u32_at = |offset: usize| -> Result<u32> {
+      data
+        .get(offset..offset + 4)
+        .map(|bytes| u32::from_be_bytes(bytes.try_into().unwrap()))
+        .ok_or_else(|| "Keystore header is truncated".into())
+    };
+    match data.first().copied() {
+      Some(id) if id == KeystoreFormat::Pbkdf2 as u8 => {
+        let iterations = u32_at(1)?;
+        if iterations == 0 || iterations > PBKDF2_MAX_ITERATIONS {
+          return Err(format!("Unsupported PBKDF2 iteration count {}", iterations).into());
+        }
+        Ok((Self::Pbkdf2 { iterations }, 5))
+      }
+      Some(id) if id == KeystoreFormat::Scrypt as u8 => {
+        let log_n = *data.get(1).ok_or("Keystore header is truncated")?;
+        let (r, p) = (u32_at(2)?, u32_at(6)?);
+        let bounded = log_n > 0 && log_n <= SCRYPT_MAX_LOG_N && r > 0 && p > 0;
+        if !bounded || r.saturating_mul(p) > SCRYPT_MAX_R_P {
+          return Err(format!("Unsupported scrypt parameters N=2^{} r={} p={}", log_n, r, p).into());
+        }
+        Ok((Self::Scrypt { log_n, r, p }, 10))
+      }
+      Some(id) => Err(format!("Unknown keystore KDF {}", id).into()),
+      None => Err("Keystore header is truncated".into()),
+    }
+  }
+}
+
+struct Keystore {
+  // None for EMIP-3 data, which has no header.
+  kdf: Option<Kdf>,
+  salt: Vec<u8>,
+  nonce: Vec<u8>,
+  tag: Vec<u8>,
+  ciphertext: Vec<u8>,
+}
+
+impl Keystore {
+  fn kdf(&self) -> Kdf {
+    self.kdf.unwrap_or(Kdf::Pbkdf2 { iterations: EMIP3_ITERATIONS })
+  }
+
+  // The versioned header is authenticated as associated data.
+  fn header(&self) -> Vec<u8> {
+    match self.kdf {
+      None => Vec::new(),
+      Some(kdf) => {
+        let mut header = MAGIC.to_vec();
+        header.push(VERSION);
+        kdf.write(&mut header);
+        header
+      }
+    }
+  }
+
+  fn seal(kdf: Option<Kdf>, password: &[u8], secret: &[u8]) -> Result<Self> {
+    if password.is_empty() {
+      return Err("Password cannot be empty".into());
+    }
+    let mut keystore = Self {
+      kdf,
+      salt: random_bytes(SALT_SIZE)?,
+      nonce: random_bytes(NONCE_SIZE)?,
+      tag: vec![0u8; TAG_SIZE],
+      ciphertext: vec![0u8; secret.len()],
+    };
+    let key = keystore.kdf().derive(password, &keystore.salt);
+    ChaCha20Poly1305::new(&key[..], &keystore.nonce, &keystore.header()).encrypt(
+      secret,
+      &mut keystore.ciphertext,
+      &mut keystore.tag,
+    );
+    Ok(keystore)
+  }
+
+  // A wrong password and tampered data are indistinguishable, the tag is
+  // compared in constant time.
+  fn open(&self, password: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+    let key = self.kdf().derive(password, &self.salt);
+    let mut secret = Zeroizing::new(vec![0u8; self.ciphertext.len()]);
+    if ChaCha20Poly1305::new(&key[..], &self.nonce, &self.header()).decrypt(
+      &self.ciphertext,
+      &mut secret[..],
+      &self.tag,
+    ) {
+      Ok(secret)
+    } else {
+      Err("Invalid password or corrupted keystore".into())
+    }
+  }
+
+  fn to_bytes(&self) -> Vec<u8> {
+    let mut bytes = self.header();
+    bytes.extend_from_slice(&self.salt);
+    bytes.extend_from_slice(&self.nonce);
+    bytes.extend_from_slice(&self.tag);
+    bytes.extend_from_slice(&self.ciphertext);
+    bytes
+  }
+
+  fn from_bytes(data: &[u8]) -> Result<Self> {
+    let (kdf, body) = if data.starts_with(MAGIC) {
+      match data.get(MAGIC.len()) {
+        Some(&VERSION) => {}
+        Some(version) => return Err(format!("Unsupported keystore version {}", version).into()),
+        None => return Err("Keystore header is truncated".into()),
+      }
+      let (kdf, length) = Kdf::read(&data[MAGIC.len() + 1..])?;
+      (Some(kdf), &data[MAGIC.len() + 1 + length..])
+    } else {
+      (None, data)
+    };
+    if body.len() != SALT_SIZE + NONCE_SIZE + TAG_SIZE + SECRET_SIZE {
+      return Err(format!("Invalid keystore length {}", data.len()).into());
+    }
+    let (salt, rest) = body.split_at(SALT_SIZE);
+    let (nonce, rest) = rest.split_at(NONCE_SIZE);
+    let (tag, ciphertext) = rest.split_at(TAG_SIZE);
+    Ok(Self {
+      kdf,
+      salt: salt.to_vec(),
+      nonce: nonce.to_vec(),
+      tag: tag.to_vec(),
+      ciphertext: ciphertext.to_vec(),
+    })
+  }
+}
+
+pub(crate) fn random_bytes(length: usize) -> Result<Vec<u8>> {
+  let mut bytes = vec![0u8; length];
+  getrandom::getrandom(&mut bytes).map_err(|e| format!("Random generator failure: {}", e))?;
+  Ok(bytes)
+}
+
+fn default_kdf(format: KeystoreFormat) -> Option<Kdf> {
+  match format {
+    KeystoreFormat::Emip3 => None,
+    KeystoreFormat::Pbkdf2 => Some(Kdf::Pbkdf2 { iterations: PBKDF2_ITERATIONS }),
+    KeystoreFormat::Scrypt => Some(Kdf::Scrypt { log_n: SCRYPT_LOG_N, r: SCRYPT_R, p: SCRYPT_P }),
+  }
+}
+
+fn decrypt(data: &[u8], password: &[u8]) -> Result<RBip32PrivateKey> {
+  Keystore::from_bytes(data)
+    .and_then(|keystore| keystore.open(password))
+    .and_then(|secret| RBip32PrivateKey::from_bytes(&secret[..]).into_result())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_bip32_private_key_encrypt(
+  key: Bip32PrivateKey, password: CData, format: KeystoreFormat, result: &mut CData,
+  error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    password.unowned().zip(key.try_into()).and_then(|(password, key): (_, RBip32PrivateKey)| {
+      let secret = Zeroizing::new(key.as_bytes());
+      Keystore::seal(default_kdf(format), password, &secret).map(|ks| ks.to_bytes().into())
+    })
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_bip32_private_key_decrypt(
+  data: CData, password: CData, result: &mut Bip32PrivateKey, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    data
+      .unowned()
+      .zip(password.unowned())
+      .and_then(|(data, password)| decrypt(data, password))
+      .map(|key| key.into())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_bip32_private_key_keystore_format(
+  data: CData, result: &mut KeystoreFormat, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    data.unowned().and_then(Keystore::from_bytes).map(|keystore| match keystore.kdf {
+      None => KeystoreFormat::Emip3,
+      Some(Kdf::Pbkdf2 { .. }) => KeystoreFormat::Pbkdf2,
+      Some(Kdf::Scrypt { .. }) => KeystoreFormat::Scrypt,
+    })
+  })
+  .response(result, error)
+}
+
+// Re-encrypts with a fresh salt and nonce, keeping the format and KDF parameters.
+#[no_mangle]
+pub unsafe extern "C" fn cardano_bip32_private_key_change_password(
+  data: CData, old_password: CData, new_password: CData, result: &mut CData, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    data
+      .unowned()
+      .zip(old_password.unowned())
+      .zip(new_password.unowned())
+      .and_then(|((data, old_password), new_password)| {
+        let keystore = Keystore::from_bytes(data)?;
+        let secret = keystore.open(old_password)?;
+        Keystore::seal(keystore.kdf, new_password, &secret)
+      })
+      .map(|keystore| keystore.to_bytes().into())
+  })
+  .response(result, error)
+}
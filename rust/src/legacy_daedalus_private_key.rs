@@ -0,0 +1,221 @@
+use crate::bip32_public_key::Bip32PublicKey;
+use crate::data::CData;
+use crate::derivation_path::{DerivationPath, HARDENED_INDEX};
+use crate::error::CError;
+use crate::hash::blake2b;
+use crate::panic::*;
+use crate::ptr::*;
+use cardano_serialization_lib::crypto::{
+  Bip32PublicKey as RBip32PublicKey, LegacyDaedalusPrivateKey as RLegacyDaedalusPrivateKey,
+};
+use cbor_event::se::Serializer;
+use cryptoxide::curve25519::Scalar;
+use cryptoxide::digest::Digest;
+use cryptoxide::ed25519;
+use cryptoxide::hmac::Hmac;
+use cryptoxide::mac::Mac;
+use cryptoxide::sha2::Sha512;
+use std::convert::{TryFrom, TryInto};
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct LegacyDaedalusPrivateKey([u8; 96]);
+
+impl TryFrom<LegacyDaedalusPrivateKey> for RLegacyDaedalusPrivateKey {
+  type Error = CError;
+
+  fn try_from(pk: LegacyDaedalusPrivateKey) -> Result<Self> {
+    Self::from_bytes(&pk.0).into_result()
+  }
+}
+
+impl From<RLegacyDaedalusPrivateKey> for LegacyDaedalusPrivateKey {
+  fn from(pk: RLegacyDaedalusPrivateKey) -> Self {
+    Self(pk.as_bytes().try_into().unwrap())
+  }
+}
+
+pub(crate) fn cbor_bytes(bytes: &[u8]) -> Result<Vec<u8>> {
+  let mut serializer = Serializer::new_vec();
+  serializer.write_bytes(bytes)?;
+  Ok(serializer.finalize())
+}
+
+// Daedalus seeds are cbor(bytes(blake2b_256(cbor(bytes(entropy))))).
+fn daedalus_seed(entropy: &[u8]) -> Result<Vec<u8>> {
+  cbor_bytes(&blake2b::<256>(&cbor_bytes(entropy)?))
+}
+
+fn from_daedalus_seed(seed: &[u8]) -> [u8; 96] {
+  let mut mac = Hmac::new(Sha512::new(), seed);
+  let mut key = [0u8; 96];
+  let mut iteration = 1;
+  loop {
+    let mut block = [0u8; 64];
+    mac.reset();
+    mac.input(format!("Root Seed Chain {}", iteration).as_bytes());
+    mac.raw_result(&mut block);
+    let mut sha = Sha512::new();
+    sha.input(&block[..32]);
+    sha.result(&mut key[..64]);
+    key[0] &= 0b1111_1000;
+    key[31] &= 0b0011_1111;
+    key[31] |= 0b0100_0000;
+    if key[31] & 0b0010_0000 == 0 {
+      key[64..].copy_from_slice(&block[32..]);
+      return key;
+    }
+    iteration += 1;
+  }
+}
+
+// V1 scheme: the carry of 8 * zl is dropped and the sum is reduced modulo
+// the group order, the right half is added byte by byte without carry.
+fn add_28_mul8_v1(x: &[u8], y: &[u8]) -> [u8; 32] {
+  let mut y8 = [0u8; 32];
+  for i in 0..32 {
+    y8[i] = y[i] << 3;
+  }
+  let mut sum = [0u8; 64];
+  let mut carry = 0u16;
+  for i in 0..32 {
+    let r = x[i] as u16 + y8[i] as u16 + carry;
+    sum[i] = r as u8;
+    carry = r >> 8;
+  }
+  sum[32] = carry as u8;
+  Scalar::reduce_from_wide_bytes(&sum).to_bytes()
+}
+
+fn add_256bits_v1(x: &[u8], y: &[u8]) -> [u8; 32] {
+  let mut out = [0u8; 32];
+  for i in 0..32 {
+    out[i] = x[i].wrapping_add(y[i]);
+  }
+  out
+}
+
+fn public_key(key: &[u8; 96]) -> [u8; 32] {
+  ed25519::extended_to_public(key[..64].try_into().unwrap())
+}
+
+fn xpub(key: &[u8; 96]) -> [u8; 64] {
+  let mut xpub = [0u8; 64];
+  xpub[..32].copy_from_slice(&public_key(key));
+  xpub[32..].copy_from_slice(&key[64..]);
+  xpub
+}
+
+fn derive_v1(key: &[u8; 96], index: u32) -> [u8; 96] {
+  let chain_code = &key[64..];
+  let mut zmac = Hmac::new(Sha512::new(), chain_code);
+  let mut imac = Hmac::new(Sha512::new(), chain_code);
+  if index >= HARDENED_INDEX {
+    zmac.input(&[0x0]);
+    zmac.input(&key[..64]);
+    imac.input(&[0x1]);
+    imac.input(&key[..64]);
+  } else {
+    let pk = public_key(key);
+    zmac.input(&[0x2]);
+    zmac.input(&pk);
+    imac.input(&[0x3]);
+    imac.input(&pk);
+  }
+  zmac.input(&index.to_be_bytes());
+  imac.input(&index.to_be_bytes());
+  let mut z = [0u8; 64];
+  let mut i = [0u8; 64];
+  zmac.raw_result(&mut z);
+  imac.raw_result(&mut i);
+  let mut child = [0u8; 96];
+  child[..32].copy_from_slice(&add_28_mul8_v1(&key[..32], &z[..32]));
+  child[32..64].copy_from_slice(&add_256bits_v1(&key[32..64], &z[32..]));
+  child[64..].copy_from_slice(&i[32..]);
+  child
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_legacy_daedalus_private_key_from_bytes(
+  data: CData, result: &mut LegacyDaedalusPrivateKey, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    data
+      .unowned()
+      .and_then(|bytes| RLegacyDaedalusPrivateKey::from_bytes(bytes).into_result())
+      .map(|pk| pk.into())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_legacy_daedalus_private_key_as_bytes(
+  pk: LegacyDaedalusPrivateKey, result: &mut CData, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    pk.try_into()
+      .map(|pk: RLegacyDaedalusPrivateKey| pk.as_bytes())
+      .map(|bytes| bytes.into())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_legacy_daedalus_private_key_chaincode(
+  pk: LegacyDaedalusPrivateKey, result: &mut CData, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    pk.try_into()
+      .map(|pk: RLegacyDaedalusPrivateKey| pk.chaincode())
+      .map(|bytes| bytes.into())
+  })
+  .response(result, error)
+}
+
+// Root key of a random (12 word) Daedalus wallet.
+#[no_mangle]
+pub unsafe extern "C" fn cardano_legacy_daedalus_private_key_from_daedalus_entropy(
+  entropy: CData, result: &mut LegacyDaedalusPrivateKey, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    entropy
+      .unowned()
+      .and_then(daedalus_seed)
+      .map(|seed| LegacyDaedalusPrivateKey(from_daedalus_seed(&seed)))
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_legacy_daedalus_private_key_derive(
+  pk: LegacyDaedalusPrivateKey, index: u32, result: &mut LegacyDaedalusPrivateKey,
+  error: &mut CError,
+) -> bool {
+  handle_exception(|| LegacyDaedalusPrivateKey(derive_v1(&pk.0, index))).response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_legacy_daedalus_private_key_derive_path(
+  pk: LegacyDaedalusPrivateKey, path: DerivationPath, result: &mut LegacyDaedalusPrivateKey,
+  error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    path
+      .unowned()
+      .map(|path| path.iter().fold(pk.0, |key, index| derive_v1(&key, *index)))
+      .map(LegacyDaedalusPrivateKey)
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_legacy_daedalus_private_key_to_public(
+  pk: LegacyDaedalusPrivateKey, result: &mut Bip32PublicKey, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    RBip32PublicKey::from_bytes(&xpub(&pk.0))
+      .into_result()
+      .map(|pk| pk.into())
+  })
+  .response(result, error)
+}
@@ -8,6 +8,11 @@ pub mod data;
 pub mod stake_credential;
 pub mod bip32_private_key;
 pub mod bip32_public_key;
+pub mod derivation_path;
+pub mod account;
+pub mod cip5;
+pub mod legacy_daedalus_private_key;
+pub mod keystore;
 pub mod ed25519_signature;
 pub mod linear_fee;
 pub mod private_key;
@@ -30,6 +35,7 @@ pub mod move_instantaneous_rewards_cert;
 pub mod certificate;
 pub mod value;
 pub mod protocol_param_update;
+pub mod protocol_parameters;
 pub mod transaction_output;
 pub mod transaction_body;
 pub mod general_transaction_metadata;
@@ -41,12 +47,21 @@ pub mod constr_plutus_data;
 pub mod plutus_map;
 pub mod plutus_list;
 pub mod transaction_metadatum_labels;
+pub mod cip25;
+pub mod cip68;
+pub mod cip36;
 pub mod transaction;
 pub mod transaction_builder;
+pub mod transaction_balance;
+pub mod transaction_verification;
 pub mod int;
 pub mod json_value;
+pub mod mnemonic;
+pub mod slip39;
+pub mod message_signing;
 pub mod transaction_unspent_output;
 mod ptr;
+mod hash;
 mod panic;
 mod array;
 
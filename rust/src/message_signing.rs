@@ -0,0 +1,541 @@
+use crate::address::address::Address;
+use crate::data::CData;
+use crate::error::CError;
+use crate::hash::blake2b;
+use crate::option::COption;
+use crate::panic::*;
+use crate::private_key::PrivateKey;
+use crate::ptr::*;
+use crate::public_key::PublicKey;
+use cardano_serialization_lib::address::{
+  Address as RAddress, BaseAddress as RBaseAddress, ByronAddress as RByronAddress,
+  EnterpriseAddress as REnterpriseAddress, PointerAddress as RPointerAddress,
+  RewardAddress as RRewardAddress, StakeCredential as RStakeCredential,
+};
+use cardano_serialization_lib::crypto::{
+  Ed25519Signature as REd25519Signature, PrivateKey as RPrivateKey, PublicKey as RPublicKey,
+};
+use cbor_event::de::Deserializer;
+use cbor_event::se::Serializer;
+use cbor_event::{Len, Special, Type, Value};
+use std::convert::{TryFrom, TryInto};
+use std::io::Cursor;
+
+const COSE_SIGN1_TAG: u64 = 18;
+const SIG_STRUCTURE_CONTEXT: &str = "Signature1";
+
+// Header and key parameter labels (RFC 8152)
+const LABEL_ALGORITHM: i64 = 1;
+const LABEL_KEY_TYPE: i64 = 1;
+const LABEL_KEY_ID: i64 = 2;
+const LABEL_KEY_ALGORITHM: i64 = 3;
+const LABEL_CURVE: i64 = -1;
+const LABEL_X: i64 = -2;
+const LABEL_ADDRESS: &str = "address";
+const LABEL_HASHED: &str = "hashed";
+
+const ALGORITHM_EDDSA: i64 = -8;
+const KEY_TYPE_OKP: i64 = 1;
+const CURVE_ED25519: i64 = 6;
+
+enum Label {
+  Int(i64),
+  Text(String),
+}
+
+fn read_label<R: std::io::BufRead>(raw: &mut Deserializer<R>) -> Result<Label> {
+  match raw.cbor_type()? {
+    Type::UnsignedInteger => Ok(Label::Int(raw.unsigned_integer()? as i64)),
+    Type::NegativeInteger => Ok(Label::Int(raw.negative_integer()?)),
+    Type::Text => Ok(Label::Text(raw.text()?)),
+    t => Err(format!("Invalid COSE label of type {:?}", t).into()),
+  }
+}
+
+fn read_int<R: std::io::BufRead>(raw: &mut Deserializer<R>) -> Result<i64> {
+  match raw.cbor_type()? {
+    Type::UnsignedInteger => Ok(raw.unsigned_integer()? as i64),
+    Type::NegativeInteger => Ok(raw.negative_integer()?),
+    t => Err(format!("Expected an integer, found {:?}", t).into()),
+  }
+}
+
+fn read_map_len<R: std::io::BufRead>(raw: &mut Deserializer<R>) -> Result<u64> {
+  match raw.map()? {
+    Len::Len(len) => Ok(len),
+    Len::Indefinite => Err("Indefinite length COSE maps are not supported".into()),
+  }
+}
+
+fn check_algorithm(algorithm: i64) -> Result<()> {
+  if algorithm == ALGORITHM_EDDSA {
+    Ok(())
+  } else {
+    Err(format!("Unsupported COSE algorithm {}, expected EdDSA ({})", algorithm, ALGORITHM_EDDSA).into())
+  }
+}
+
+pub(crate) struct RCoseSign1 {
+  protected_headers: Vec<u8>,
+  address: Option<Vec<u8>>,
+  hashed: bool,
+  payload: Option<Vec<u8>>,
+  signature: Vec<u8>,
+}
+
+impl RCoseSign1 {
+  fn protected_headers(address: Option<&[u8]>) -> Result<Vec<u8>> {
+    let mut serializer = Serializer::new_vec();
+    serializer.write_map(Len::Len(if address.is_some() { 2 } else { 1 }))?;
+    serializer.write_unsigned_integer(LABEL_ALGORITHM as u64)?;
+    serializer.write_negative_integer(ALGORITHM_EDDSA)?;
+    if let Some(address) = address {
+      serializer.write_text(LABEL_ADDRESS)?.write_bytes(address)?;
+    }
+    Ok(serializer.finalize())
+  }
+
+  // Sig_structure = ["Signature1", protected, external_aad, payload]
+  fn signed_data(&self, external_aad: &[u8]) -> Result<Vec<u8>> {
+    let payload = self.payload.as_ref().ok_or("COSE_Sign1 payload is detached")?;
+    let mut serializer = Serializer::new_vec();
+    serializer
+      .write_array(Len::Len(4))?
+      .write_text(SIG_STRUCTURE_CONTEXT)?
+      .write_bytes(&self.protected_headers)?
+      .write_bytes(external_aad)?
+      .write_bytes(payload)?;
+    Ok(serializer.finalize())
+  }
+
+  fn sign(
+    key: &RPrivateKey, payload: &[u8], external_aad: &[u8], address: Option<Vec<u8>>, hashed: bool,
+  ) -> Result<Self> {
+    let mut sign1 = Self {
+      protected_headers: Self::protected_headers(address.as_deref())?,
+      address,
+      hashed,
+      payload: Some(if hashed { blake2b::<224>(payload) } else { payload.to_vec() }),
+      signature: Vec::new(),
+    };
+    sign1.signature = key.sign(&sign1.signed_data(external_aad)?).to_bytes();
+    Ok(sign1)
+  }
+
+  fn verify(&self, external_aad: &[u8], key: &RPublicKey) -> Result<bool> {
+    let signature = REd25519Signature::from_bytes(self.signature.clone()).into_result()?;
+    self.signed_data(external_aad).map(|data| key.verify(&data, &signature))
+  }
+
+  fn covers(&self, payload: &[u8]) -> bool {
+    match &self.payload {
+      Some(signed) if self.hashed => *signed == blake2b::<224>(payload),
+      Some(signed) => signed == payload,
+      None => false,
+    }
+  }
+
+  fn to_bytes(&self) -> Result<Vec<u8>> {
+    let mut serializer = Serializer::new_vec();
+    serializer
+      .write_array(Len::Len(4))?
+      .write_bytes(&self.protected_headers)?
+      .write_map(Len::Len(1))?
+      .write_text(LABEL_HASHED)?
+      .write_special(Special::Bool(self.hashed))?;
+    match &self.payload {
+      Some(payload) => serializer.write_bytes(payload)?,
+      None => serializer.write_special(Special::Null)?,
+    };
+    serializer.write_bytes(&self.signature)?;
+    Ok(serializer.finalize())
+  }
+
+  fn from_bytes(bytes: &[u8]) -> Result<Self> {
+    let mut raw = Deserializer::from(Cursor::new(bytes));
+    if raw.cbor_type()? == Type::Tag {
+      let tag = raw.tag()?;
+      if tag != COSE_SIGN1_TAG {
+        return Err(format!("Unexpected tag {}, expected COSE_Sign1 ({})", tag, COSE_SIGN1_TAG).into());
+      }
+    }
+    raw.tuple(4, "COSE_Sign1")?;
+    let protected_headers = raw.bytes()?;
+    let address = Self::read_protected_headers(&protected_headers)?;
+    let mut hashed = false;
+    for _ in 0..read_map_len(&mut raw)? {
+      match read_label(&mut raw)? {
+        Label::Text(label) if label == LABEL_HASHED => hashed = raw.bool()?,
+        _ => {
+          raw.deserialize::<Value>()?;
+        }
+      }
+    }
+    let payload = match raw.cbor_type()? {
+      Type::Special => match raw.special()? {
+        Special::Null => None,
+        special => return Err(format!("Invalid COSE_Sign1 payload {:?}", special).into()),
+      },
+      _ => Some(raw.bytes()?),
+    };
+    let signature = raw.bytes()?;
+    Ok(Self { protected_headers, address, hashed, payload, signature })
+  }
+
+  fn read_protected_headers(bytes: &[u8]) -> Result<Option<Vec<u8>>> {
+    let mut address = None;
+    if bytes.is_empty() {
+      return Ok(address);
+    }
+    let mut raw = Deserializer::from(Cursor::new(bytes));
+    for _ in 0..read_map_len(&mut raw)? {
+      match read_label(&mut raw)? {
+        Label::Int(LABEL_ALGORITHM) => check_algorithm(read_int(&mut raw)?)?,
+        Label::Text(label) if label == LABEL_ADDRESS => address = Some(raw.bytes()?),
+        _ => {
+          raw.deserialize::<Value>()?;
+        }
+      }
+    }
+    Ok(address)
+  }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct CoseSign1 {
+  protected_headers: CData,
+  address: COption<CData>,
+  hashed: bool,
+  payload: COption<CData>,
+  signature: CData,
+}
+
+impl Free for CoseSign1 {
+  unsafe fn free(&mut self) {
+    self.protected_headers.free();
+    self.address.free();
+    self.payload.free();
+    self.signature.free();
+  }
+}
+
+impl From<RCoseSign1> for CoseSign1 {
+  fn from(sign1: RCoseSign1) -> Self {
+    Self {
+      protected_headers: sign1.protected_headers.into(),
+      address: sign1.address.map(|address| address.into()).into(),
+      hashed: sign1.hashed,
+      payload: sign1.payload.map(|payload| payload.into()).into(),
+      signature: sign1.signature.into(),
+    }
+  }
+}
+
+impl TryFrom<CoseSign1> for RCoseSign1 {
+  type Error = CError;
+
+  fn try_from(sign1: CoseSign1) -> Result<Self> {
+    let optional = |data: COption<CData>| -> Result<Option<Vec<u8>>> {
+      match data {
+        COption::Some(data) => unsafe { data.unowned() }.map(|data| Some(data.to_vec())),
+        COption::None => Ok(None),
+      }
+    };
+    let protected_headers = unsafe { sign1.protected_headers.unowned()? }.to_vec();
+    let address = optional(sign1.address)?;
+    if address != Self::read_protected_headers(&protected_headers)? {
+      return Err("COSE_Sign1 address does not match its protected headers".into());
+    }
+    Ok(Self {
+      protected_headers,
+      address,
+      hashed: sign1.hashed,
+      payload: optional(sign1.payload)?,
+      signature: unsafe { sign1.signature.unowned()? }.to_vec(),
+    })
+  }
+}
+
+pub(crate) struct RCoseKey {
+  key_id: Option<Vec<u8>>,
+  public_key: RPublicKey,
+}
+
+impl RCoseKey {
+  fn to_bytes(&self) -> Result<Vec<u8>> {
+    let mut serializer = Serializer::new_vec();
+    serializer.write_map(Len::Len(if self.key_id.is_some() { 5 } else { 4 }))?;
+    serializer.write_unsigned_integer(LABEL_KEY_TYPE as u64)?;
+    serializer.write_unsigned_integer(KEY_TYPE_OKP as u64)?;
+    if let Some(key_id) = &self.key_id {
+      serializer.write_unsigned_integer(LABEL_KEY_ID as u64)?.write_bytes(key_id)?;
+    }
+    serializer.write_unsigned_integer(LABEL_KEY_ALGORITHM as u64)?;
+    serializer.write_negative_integer(ALGORITHM_EDDSA)?;
+    serializer.write_negative_integer(LABEL_CURVE)?;
+    serializer.write_unsigned_integer(CURVE_ED25519 as u64)?;
+    serializer.write_negative_integer(LABEL_X)?.write_bytes(self.public_key.as_bytes())?;
+    Ok(serializer.finalize())
+  }
+
+  fn from_bytes(bytes: &[u8]) -> Result<Self> {
+    let mut raw = Deserializer::from(Cursor::new(bytes));
+    let mut key_type = None;
+    let mut curve = None;
+    let mut key_id = None;
+    let mut x = None;
+    for _ in 0..read_map_len(&mut raw)? {
+      match read_label(&mut raw)? {
+        Label::Int(LABEL_KEY_TYPE) => key_type = Some(read_int(&mut raw)?),
+        Label::Int(LABEL_KEY_ID) => key_id = Some(raw.bytes()?),
+        Label::Int(LABEL_KEY_ALGORITHM) => check_algorithm(read_int(&mut raw)?)?,
+        Label::Int(LABEL_CURVE) => curve = Some(read_int(&mut raw)?),
+        Label::Int(LABEL_X) => x = Some(raw.bytes()?),
+        _ => {
+          raw.deserialize::<Value>()?;
+        }
+      }
+    }
+    if key_type != Some(KEY_TYPE_OKP) {
+      return Err(format!("Unsupported COSE_Key type {:?}, expected OKP ({})", key_type, KEY_TYPE_OKP).into());
+    }
+    if curve != Some(CURVE_ED25519) {
+      return Err(format!("Unsupported COSE_Key curve {:?}, expected Ed25519 ({})", curve, CURVE_ED25519).into());
+    }
+    let x = x.ok_or("COSE_Key has no public key (-2)")?;
+    let public_key = RPublicKey::from_bytes(&x).into_result()?;
+    Ok(Self { key_id, public_key })
+  }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct CoseKey {
+  key_id: COption<CData>,
+  public_key: PublicKey,
+}
+
+impl Free for CoseKey {
+  unsafe fn free(&mut self) {
+    self.key_id.free();
+  }
+}
+
+impl From<RCoseKey> for CoseKey {
+  fn from(key: RCoseKey) -> Self {
+    Self {
+      key_id: key.key_id.map(|key_id| key_id.into()).into(),
+      public_key: key.public_key.into(),
+    }
+  }
+}
+
+impl TryFrom<CoseKey> for RCoseKey {
+  type Error = CError;
+
+  fn try_from(key: CoseKey) -> Result<Self> {
+    let key_id = match key.key_id {
+      COption::Some(key_id) => Some(unsafe { key_id.unowned()? }.to_vec()),
+      COption::None => None,
+    };
+    key.public_key.try_into().map(|public_key| Self { key_id, public_key })
+  }
+}
+
+// CIP-30 signData result: COSE_Sign1 and COSE_Key CBOR bytes.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct DataSignature {
+  signature: CData,
+  key: CData,
+}
+
+impl Free for DataSignature {
+  unsafe fn free(&mut self) {
+    self.signature.free();
+    self.key.free();
+  }
+}
+
+// The credential the signing key has to hash to: the payment key, or the
+// stake key for reward addresses.
+fn signing_credential(address: &RAddress) -> Result<RStakeCredential> {
+  if RByronAddress::from_address(address).is_some() {
+    return Err("Byron addresses can't be verified with a COSE_Key".into());
+  }
+  RBaseAddress::from_address(address)
+    .map(|base| base.payment_cred())
+    .or_else(|| REnterpriseAddress::from_address(address).map(|ent| ent.payment_cred()))
+    .or_else(|| RPointerAddress::from_address(address).map(|ptr| ptr.payment_cred()))
+    .or_else(|| RRewardAddress::from_address(address).map(|reward| reward.payment_cred()))
+    .ok_or_else(|| "Unsupported address type".into())
+}
+
+fn verify_data_signature(
+  signature: &[u8], key: &[u8], address: &RAddress, payload: &[u8], external_aad: &[u8],
+) -> Result<bool> {
+  let sign1 = RCoseSign1::from_bytes(signature)?;
+  let key = RCoseKey::from_bytes(key)?;
+  let key_hash = signing_credential(address)?
+    .to_keyhash()
+    .ok_or("Script credential addresses can't be verified with a COSE_Key")?;
+  if key_hash.to_bytes() != blake2b::<224>(&key.public_key.as_bytes())
+    || sign1.address != Some(address.to_bytes())
+    || !sign1.covers(payload)
+  {
+    return Ok(false);
+  }
+  sign1.verify(external_aad, &key.public_key)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_cose_sign1_make(
+  payload: CData, external_aad: CData, address: Address, hashed: bool, key: PrivateKey,
+  result: &mut CoseSign1, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    payload
+      .unowned()
+      .zip(external_aad.unowned())
+      .zip(address.try_into())
+      .zip(key.try_into())
+      .and_then(|(((payload, external_aad), address), key): ((_, RAddress), RPrivateKey)| {
+        RCoseSign1::sign(&key, payload, external_aad, Some(address.to_bytes()), hashed)
+      })
+      .map(|sign1| sign1.into())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_cose_sign1_signed_data(
+  sign1: CoseSign1, external_aad: CData, result: &mut CData, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    sign1
+      .try_into()
+      .zip(external_aad.unowned())
+      .and_then(|(sign1, external_aad): (RCoseSign1, _)| sign1.signed_data(external_aad))
+      .map(|data| data.into())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_cose_sign1_verify(
+  sign1: CoseSign1, external_aad: CData, public_key: PublicKey, result: &mut bool,
+  error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    sign1
+      .try_into()
+      .zip(external_aad.unowned())
+      .zip(public_key.try_into())
+      .and_then(|((sign1, external_aad), public_key): ((RCoseSign1, _), RPublicKey)| {
+        sign1.verify(external_aad, &public_key)
+      })
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_cose_sign1_from_bytes(
+  data: CData, result: &mut CoseSign1, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    data
+      .unowned()
+      .and_then(RCoseSign1::from_bytes)
+      .map(|sign1| sign1.into())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_cose_sign1_to_bytes(
+  sign1: CoseSign1, result: &mut CData, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    sign1
+      .try_into()
+      .and_then(|sign1: RCoseSign1| sign1.to_bytes())
+      .map(|bytes| bytes.into())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_cose_sign1_free(sign1: &mut CoseSign1) {
+  sign1.free()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_cose_key_from_bytes(
+  data: CData, result: &mut CoseKey, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| data.unowned().and_then(RCoseKey::from_bytes).map(|key| key.into()))
+    .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_cose_key_to_bytes(
+  key: CoseKey, result: &mut CData, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    key
+      .try_into()
+      .and_then(|key: RCoseKey| key.to_bytes())
+      .map(|bytes| bytes.into())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_cose_key_free(key: &mut CoseKey) {
+  key.free()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_data_signature_sign(
+  address: Address, payload: CData, key: PrivateKey, result: &mut DataSignature,
+  error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    address
+      .try_into()
+      .zip(payload.unowned())
+      .zip(key.try_into())
+      .and_then(|((address, payload), key): ((RAddress, _), RPrivateKey)| {
+        let sign1 = RCoseSign1::sign(&key, payload, &[], Some(address.to_bytes()), false)?;
+        let cose_key = RCoseKey { key_id: None, public_key: key.to_public() };
+        Ok(DataSignature { signature: sign1.to_bytes()?.into(), key: cose_key.to_bytes()?.into() })
+      })
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_data_signature_verify(
+  signature: DataSignature, address: Address, payload: CData, external_aad: CData,
+  result: &mut bool, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    signature
+      .signature
+      .unowned()
+      .zip(signature.key.unowned())
+      .zip(address.try_into())
+      .zip(payload.unowned())
+      .zip(external_aad.unowned())
+      .and_then(|((((signature, key), address), payload), external_aad)| {
+        verify_data_signature(signature, key, &address, payload, external_aad)
+      })
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_data_signature_free(signature: &mut DataSignature) {
+  signature.free()
+}
@@ -0,0 +1,235 @@
+use crate::array::CArray;
+use crate::data::CData;
+use crate::error::CError;
+use crate::json_value::WrappedCharPtr;
+use crate::panic::*;
+use crate::ptr::*;
+use crate::string::{CharPtr, IntoCString};
+use bip39::{Error as RMnemonicError, Language as RLanguage, Mnemonic as RMnemonic};
+
+const MAX_SUGGESTIONS: usize = 5;
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub enum MnemonicLanguage {
+  English,
+  ChineseSimplified,
+  ChineseTraditional,
+  Czech,
+  French,
+  Italian,
+  Japanese,
+  Korean,
+  Portuguese,
+  Spanish,
+}
+
+impl From<MnemonicLanguage> for RLanguage {
+  fn from(language: MnemonicLanguage) -> Self {
+    match language {
+      MnemonicLanguage::English => Self::English,
+      MnemonicLanguage::ChineseSimplified => Self::SimplifiedChinese,
+      MnemonicLanguage::ChineseTraditional => Self::TraditionalChinese,
+      MnemonicLanguage::Czech => Self::Czech,
+      MnemonicLanguage::French => Self::French,
+      MnemonicLanguage::Italian => Self::Italian,
+      MnemonicLanguage::Japanese => Self::Japanese,
+      MnemonicLanguage::Korean => Self::Korean,
+      MnemonicLanguage::Portuguese => Self::Portuguese,
+      MnemonicLanguage::Spanish => Self::Spanish,
+    }
+  }
+}
+
+impl From<RLanguage> for MnemonicLanguage {
+  fn from(language: RLanguage) -> Self {
+    match language {
+      RLanguage::English => Self::English,
+      RLanguage::SimplifiedChinese => Self::ChineseSimplified,
+      RLanguage::TraditionalChinese => Self::ChineseTraditional,
+      RLanguage::Czech => Self::Czech,
+      RLanguage::French => Self::French,
+      RLanguage::Italian => Self::Italian,
+      RLanguage::Japanese => Self::Japanese,
+      RLanguage::Korean => Self::Korean,
+      RLanguage::Portuguese => Self::Portuguese,
+      RLanguage::Spanish => Self::Spanish,
+    }
+  }
+}
+
+// BIP39 asks for ideographic spaces between Japanese words.
+fn mnemonic_phrase(mnemonic: &RMnemonic) -> String {
+  let separator = match mnemonic.language() {
+    RLanguage::Japanese => "\u{3000}",
+    _ => " ",
+  };
+  mnemonic.words().collect::<Vec<&str>>().join(separator)
+}
+
+fn levenshtein(lhs: &str, rhs: &str) -> usize {
+  let rhs: Vec<char> = rhs.chars().collect();
+  let mut row: Vec<usize> = (0..=rhs.len()).collect();
+  for (i, lc) in lhs.chars().enumerate() {
+    let mut prev = row[0];
+    row[0] = i + 1;
+    for (j, rc) in rhs.iter().enumerate() {
+      let current = row[j + 1];
+      row[j + 1] = if lc == *rc { prev } else { 1 + prev.min(row[j]).min(row[j + 1]) };
+      prev = current;
+    }
+  }
+  row[rhs.len()]
+}
+
+// Words sharing the prefix come first (BIP39 lists are unique in their
+// first four letters), then the closest words by edit distance.
+fn suggestions(language: RLanguage, word: &str) -> Vec<&'static str> {
+  let prefix: String = word.chars().take(4).collect();
+  let mut suggestions: Vec<&'static str> = if prefix.chars().count() == 4 {
+    language.words_by_prefix(&prefix).to_vec()
+  } else {
+    Vec::new()
+  };
+  let max_distance = (word.chars().count() / 3).max(1);
+  let mut close: Vec<(usize, &'static str)> = language
+    .word_list()
+    .iter()
+    .map(|candidate| (levenshtein(word, candidate), *candidate))
+    .filter(|(distance, candidate)| *distance <= max_distance && !suggestions.contains(candidate))
+    .collect();
+  close.sort();
+  suggestions.extend(close.into_iter().map(|(_, candidate)| candidate));
+  suggestions.truncate(MAX_SUGGESTIONS);
+  suggestions
+}
+
+pub type MnemonicWords = CArray<WrappedCharPtr>;
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct MnemonicUnknownWord {
+  index: usize,
+  suggestions: MnemonicWords,
+}
+
+impl Free for MnemonicUnknownWord {
+  unsafe fn free(&mut self) {
+    self.suggestions.free()
+  }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub enum MnemonicValidation {
+  ValidKind,
+  BadWordCountKind(usize),
+  UnknownWordKind(MnemonicUnknownWord),
+  InvalidChecksumKind,
+}
+
+impl Free for MnemonicValidation {
+  unsafe fn free(&mut self) {
+    if let MnemonicValidation::UnknownWordKind(unknown_word) = self {
+      unknown_word.free()
+    }
+  }
+}
+
+fn validate(mnemonic: &str, language: RLanguage) -> Result<MnemonicValidation> {
+  match RMnemonic::parse_in(language, mnemonic) {
+    Ok(_) => Ok(MnemonicValidation::ValidKind),
+    Err(RMnemonicError::BadWordCount(count)) => Ok(MnemonicValidation::BadWordCountKind(count)),
+    Err(RMnemonicError::InvalidChecksum) => Ok(MnemonicValidation::InvalidChecksumKind),
+    Err(RMnemonicError::UnknownWord(index)) => {
+      let mut normalized = std::borrow::Cow::Borrowed(mnemonic);
+      RMnemonic::normalize_utf8_cow(&mut normalized);
+      let word = normalized.split_whitespace().nth(index).unwrap_or_default();
+      let suggestions: Vec<WrappedCharPtr> =
+        suggestions(language, word).into_iter().map(|word| word.into()).collect();
+      Ok(MnemonicValidation::UnknownWordKind(MnemonicUnknownWord {
+        index,
+        suggestions: suggestions.into(),
+      }))
+    }
+    Err(err) => Err(err.to_string().into()),
+  }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_mnemonic_generate(
+  word_count: usize, language: MnemonicLanguage, result: &mut CharPtr, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    RMnemonic::generate_in(language.into(), word_count)
+      .map_err(|err| err.to_string().into())
+      .map(|mnemonic| mnemonic_phrase(&mnemonic).into_cstr())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_mnemonic_from_entropy(
+  entropy: CData, language: MnemonicLanguage, result: &mut CharPtr, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    entropy
+      .unowned()
+      .and_then(|entropy| {
+        RMnemonic::from_entropy_in(language.into(), entropy).map_err(|err| err.to_string().into())
+      })
+      .map(|mnemonic| mnemonic_phrase(&mnemonic).into_cstr())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_mnemonic_to_entropy(
+  mnemonic: CharPtr, language: MnemonicLanguage, result: &mut CData, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    mnemonic
+      .unowned()
+      .and_then(|mnemonic| {
+        RMnemonic::parse_in(language.into(), mnemonic).map_err(|err| err.to_string().into())
+      })
+      .map(|mnemonic| mnemonic.to_entropy().into())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_mnemonic_validate(
+  mnemonic: CharPtr, language: MnemonicLanguage, result: &mut MnemonicValidation,
+  error: &mut CError,
+) -> bool {
+  handle_exception_result(|| mnemonic.unowned().and_then(|mnemonic| validate(mnemonic, language.into())))
+    .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_mnemonic_validation_free(validation: &mut MnemonicValidation) {
+  validation.free()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_mnemonic_language_of(
+  mnemonic: CharPtr, result: &mut MnemonicLanguage, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    mnemonic
+      .unowned()
+      .and_then(|mnemonic| {
+        let mut normalized = std::borrow::Cow::Borrowed(mnemonic);
+        RMnemonic::normalize_utf8_cow(&mut normalized);
+        RMnemonic::language_of(normalized).map_err(|err| err.to_string().into())
+      })
+      .map(|language| language.into())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_mnemonic_words_free(words: &mut MnemonicWords) {
+  words.free()
+}
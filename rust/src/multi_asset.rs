@@ -1,12 +1,21 @@
 use super::array::*;
+use super::asset_name::AssetName;
 use super::assets::Assets;
+use super::cip5;
 use super::error::CError;
+use super::hash::blake2b;
+use super::option::COption;
 use super::panic::*;
-use super::ptr::Free;
+use super::ptr::*;
 use super::stake_credential::ScriptHash;
-use cardano_serialization_lib::MultiAsset as RMultiAsset;
+use super::string::{CharPtr, IntoCString};
+use cardano_serialization_lib::{
+  crypto::ScriptHash as RScriptHash, AssetName as RAssetName, MultiAsset as RMultiAsset,
+};
 use std::convert::{TryFrom, TryInto};
 
+const FINGERPRINT_SIZE: usize = 20;
+
 pub type PolicyID = ScriptHash;
 pub type MultiAssetKeyValue = CKeyValue<PolicyID, Assets>;
 pub type MultiAsset = CArray<MultiAssetKeyValue>;
@@ -15,6 +24,18 @@ impl Free for PolicyID {
   unsafe fn free(&mut self) {}
 }
 
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct MultiAssetEntry {
+  policy_id: PolicyID,
+  asset_name: AssetName,
+  amount: u64,
+}
+
+impl Free for MultiAssetEntry {
+  unsafe fn free(&mut self) {}
+}
+
 impl TryFrom<MultiAsset> for RMultiAsset {
   type Error = CError;
 
@@ -68,3 +89,122 @@ pub unsafe extern "C" fn cardano_multi_asset_sub(
 pub unsafe extern "C" fn cardano_multi_asset_free(multi_asset: &mut MultiAsset) {
   multi_asset.free()
 }
+
+// CIP-14: blake2b-160 of the policy id followed by the asset name.
+fn fingerprint(policy_id: PolicyID, asset_name: AssetName) -> Result<Vec<u8>> {
+  let policy_id: RScriptHash = policy_id.into();
+  let asset_name: RAssetName = asset_name.try_into()?;
+  let mut data = policy_id.to_bytes();
+  data.extend_from_slice(&asset_name.name());
+  Ok(blake2b::<{ FINGERPRINT_SIZE * 8 }>(&data))
+}
+
+fn find_by_fingerprint(
+  multi_asset: MultiAsset, fingerprint_bech32: &str,
+) -> Result<Option<MultiAssetEntry>> {
+  let expected = cip5::decode(cip5::ASSET, fingerprint_bech32)?;
+  if expected.len() != FINGERPRINT_SIZE {
+    return Err(format!("Invalid asset fingerprint length {}", expected.len()).into());
+  }
+  for entry in unsafe { multi_asset.unowned()? } {
+    for asset in unsafe { entry.val.unowned()? } {
+      if fingerprint(entry.key, asset.key)? == expected {
+        return Ok(Some(MultiAssetEntry {
+          policy_id: entry.key,
+          asset_name: asset.key,
+          amount: asset.val,
+        }));
+      }
+    }
+  }
+  Ok(None)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_asset_fingerprint(
+  policy_id: PolicyID, asset_name: AssetName, result: &mut CharPtr, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    fingerprint(policy_id, asset_name)
+      .and_then(|hash| cip5::encode(cip5::ASSET, &hash))
+      .map(|bech32| bech32.into_cstr())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_multi_asset_find_by_fingerprint(
+  multi_asset: MultiAsset, fingerprint: CharPtr, result: &mut COption<MultiAssetEntry>,
+  error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    fingerprint
+      .unowned()
+      .and_then(|fingerprint| find_by_fingerprint(multi_asset, fingerprint))
+      .map(|entry| entry.into())
+  })
+  .response(result, error)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn fingerprint_bech32(policy_id: &str, asset_name: &str) -> String {
+    let policy_id = RScriptHash::from_bytes(hex::decode(policy_id).unwrap()).unwrap();
+    let asset_name = RAssetName::new(hex::decode(asset_name).unwrap()).unwrap();
+    fingerprint(policy_id.try_into().unwrap(), asset_name.try_into().unwrap())
+      .and_then(|hash| cip5::encode(cip5::ASSET, &hash))
+      .unwrap()
+  }
+
+  // Test vectors of CIP-14.
+  #[test]
+  fn cip14_fingerprints() {
+    let vectors = [
+      (
+        "7eae28af2208be856f7a119668ae52a49b73725e326dc16579dcc373",
+        "",
+        "asset1rjklcrnsdzqp65wjgrg55sy9723kw09mlgvlc3",
+      ),
+      (
+        "7eae28af2208be856f7a119668ae52a49b73725e326dc16579dcc37e",
+        "",
+        "asset1nl0puwxmhas8fawxp8nx4e2q3wekg969n2auw3",
+      ),
+      (
+        "1e349c9bdea19fd6c147626a5260bc44b71635f398b67c59881df209",
+        "",
+        "asset1uyuxku60yqe57nusqzjx38aan3f2wq6s93f6ea",
+      ),
+      (
+        "7eae28af2208be856f7a119668ae52a49b73725e326dc16579dcc373",
+        "504154415445",
+        "asset13n25uv0yaf5kus35fm2k86cqy60z58d9xmde92",
+      ),
+      (
+        "1e349c9bdea19fd6c147626a5260bc44b71635f398b67c59881df209",
+        "504154415445",
+        "asset1hv4p5tv2a837mzqrst04d0dcptdjmluqvdx9k3",
+      ),
+      (
+        "1e349c9bdea19fd6c147626a5260bc44b71635f398b67c59881df209",
+        "7eae28af2208be856f7a119668ae52a49b73725e326dc16579dcc373",
+        "asset1aqrdypg669jgazruv5ah07nuyqe0wxjhe2el6f",
+      ),
+      (
+        "7eae28af2208be856f7a119668ae52a49b73725e326dc16579dcc373",
+        "1e349c9bdea19fd6c147626a5260bc44b71635f398b67c59881df209",
+        "asset17jd78wukhtrnmjh3fngzasxm8rck0l2r4hhyyt",
+      ),
+      (
+        "7eae28af2208be856f7a119668ae52a49b73725e326dc16579dcc373",
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "asset1pkpwyknlvul7az0xx8czhl60pyel45rpje4z8w",
+      ),
+    ];
+    for (policy_id, asset_name, expected) in vectors {
+      assert_eq!(fingerprint_bech32(policy_id, asset_name), expected);
+    }
+  }
+}
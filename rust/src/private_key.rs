@@ -8,6 +8,7 @@ use cardano_serialization_lib::{
   crypto::PrivateKey as RPrivateKey, impl_mockchain::key::EitherEd25519SecretKey,
 };
 use std::convert::{TryFrom, TryInto};
+use zeroize::{Zeroize, Zeroizing};
 
 pub const EXTENDED_PRIVATE_KEY_LENGTH: usize = 64;
 pub const NORMAL_PRIVATE_KEY_LENGTH: usize = 32;
@@ -19,14 +20,26 @@ pub enum PrivateKey {
   Normal([u8; NORMAL_PRIVATE_KEY_LENGTH]),
 }
 
+impl Zeroize for PrivateKey {
+  fn zeroize(&mut self) {
+    match self {
+      PrivateKey::Extended(bytes) => bytes.zeroize(),
+      PrivateKey::Normal(bytes) => bytes.zeroize(),
+    }
+  }
+}
+
+// Consumes the by-value copy, which is wiped once the key is parsed.
 impl TryFrom<PrivateKey> for RPrivateKey {
   type Error = CError;
 
-  fn try_from(private_key: PrivateKey) -> Result<Self> {
-    match private_key {
-      PrivateKey::Extended(bytes) => RPrivateKey::from_extended_bytes(&bytes).into_result(),
-      PrivateKey::Normal(bytes) => RPrivateKey::from_normal_bytes(&bytes).into_result(),
-    }
+  fn try_from(mut private_key: PrivateKey) -> Result<Self> {
+    let result = match &private_key {
+      PrivateKey::Extended(bytes) => RPrivateKey::from_extended_bytes(bytes).into_result(),
+      PrivateKey::Normal(bytes) => RPrivateKey::from_normal_bytes(bytes).into_result(),
+    };
+    private_key.zeroize();
+    result
   }
 }
 
@@ -35,11 +48,11 @@ struct TPrivateKey(EitherEd25519SecretKey);
 
 impl From<RPrivateKey> for PrivateKey {
   fn from(private_key: RPrivateKey) -> Self {
-    let bytes = private_key.as_bytes();
+    let bytes = Zeroizing::new(private_key.as_bytes());
     let tpkey: TPrivateKey = unsafe { std::mem::transmute(private_key) };
     match tpkey.0 {
-      EitherEd25519SecretKey::Extended(_) => PrivateKey::Extended(bytes.try_into().unwrap()),
-      EitherEd25519SecretKey::Normal(_) => PrivateKey::Normal(bytes.try_into().unwrap()),
+      EitherEd25519SecretKey::Extended(_) => PrivateKey::Extended(bytes[..].try_into().unwrap()),
+      EitherEd25519SecretKey::Normal(_) => PrivateKey::Normal(bytes[..].try_into().unwrap()),
     }
   }
 }
@@ -56,6 +69,7 @@ pub unsafe extern "C" fn cardano_private_key_to_public(
   .response(result, error)
 }
 
+// Copies the secret into `result`, release it with cardano_data_wipe.
 #[no_mangle]
 pub unsafe extern "C" fn cardano_private_key_as_bytes(
   private_key: PrivateKey, result: &mut CData, error: &mut CError,
@@ -107,3 +121,8 @@ pub unsafe extern "C" fn cardano_private_key_sign(
   })
   .response(result, error)
 }
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_private_key_wipe(private_key: &mut PrivateKey) {
+  private_key.zeroize()
+}
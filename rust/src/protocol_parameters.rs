@@ -0,0 +1,500 @@
+use crate::array::{CArray, CKeyValue};
+use crate::error::CError;
+use crate::genesis_key_delegation::GenesisHash;
+use crate::linear_fee::Coin;
+use crate::panic::*;
+use crate::pool_registration::UnitInterval;
+use crate::protocol_param_update::{
+  Costmdls, ExUnitPrices, ExUnits, Nonce, ProtocolParamUpdate, ProtocolVersion, Rational,
+};
+use crate::ptr::*;
+use crate::transaction_body::{Epoch, ProposedProtocolParameterUpdates};
+use cardano_serialization_lib::{
+  crypto::Nonce as RNonce,
+  plutus::{Costmdls as RCostmdls, ExUnitPrices as RExUnitPrices, ExUnits as RExUnits},
+  utils::{from_bignum, BigNum as RBigNum},
+  ProposedProtocolParameterUpdates as RProposedProtocolParameterUpdates,
+  ProtocolParamUpdate as RProtocolParamUpdate, ProtocolVersion as RProtocolVersion,
+  UnitInterval as RUnitInterval,
+};
+use std::convert::{TryFrom, TryInto};
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct ProtocolParameters {
+  minfee_a: Coin,
+  minfee_b: Coin,
+  max_block_body_size: u32,
+  max_tx_size: u32,
+  max_block_header_size: u32,
+  key_deposit: Coin,
+  pool_deposit: Coin,
+  max_epoch: Epoch,
+  n_opt: u32,
+  pool_pledge_influence: Rational,
+  expansion_rate: UnitInterval,
+  treasury_growth_rate: UnitInterval,
+  d: UnitInterval,
+  extra_entropy: Nonce,
+  protocol_version: ProtocolVersion,
+  min_pool_cost: Coin,
+  ada_per_utxo_byte: Coin,
+  cost_models: Costmdls,
+  execution_costs: ExUnitPrices,
+  max_tx_ex_units: ExUnits,
+  max_block_ex_units: ExUnits,
+  max_value_size: u32,
+}
+
+impl Free for ProtocolParameters {
+  unsafe fn free(&mut self) {
+    self.cost_models.free()
+  }
+}
+
+// cardano-serialization-lib has no full parameter set, so keep our own
+// Rust-side counterpart to compare and merge values with.
+#[derive(Clone)]
+pub(crate) struct RProtocolParameters {
+  pub(crate) minfee_a: Coin,
+  pub(crate) minfee_b: Coin,
+  pub(crate) max_block_body_size: u32,
+  pub(crate) max_tx_size: u32,
+  pub(crate) max_block_header_size: u32,
+  pub(crate) key_deposit: Coin,
+  pub(crate) pool_deposit: Coin,
+  pub(crate) max_epoch: Epoch,
+  pub(crate) n_opt: u32,
+  pub(crate) pool_pledge_influence: RUnitInterval,
+  pub(crate) expansion_rate: RUnitInterval,
+  pub(crate) treasury_growth_rate: RUnitInterval,
+  pub(crate) d: RUnitInterval,
+  pub(crate) extra_entropy: RNonce,
+  pub(crate) protocol_version: RProtocolVersion,
+  pub(crate) min_pool_cost: Coin,
+  pub(crate) ada_per_utxo_byte: Coin,
+  pub(crate) cost_models: RCostmdls,
+  pub(crate) execution_costs: RExUnitPrices,
+  pub(crate) max_tx_ex_units: RExUnits,
+  pub(crate) max_block_ex_units: RExUnits,
+  pub(crate) max_value_size: u32,
+}
+
+impl TryFrom<ProtocolParameters> for RProtocolParameters {
+  type Error = CError;
+
+  fn try_from(pp: ProtocolParameters) -> Result<Self> {
+    pp.extra_entropy
+      .try_into()
+      .zip(pp.cost_models.try_into())
+      .map(|(extra_entropy, cost_models)| Self {
+        minfee_a: pp.minfee_a,
+        minfee_b: pp.minfee_b,
+        max_block_body_size: pp.max_block_body_size,
+        max_tx_size: pp.max_tx_size,
+        max_block_header_size: pp.max_block_header_size,
+        key_deposit: pp.key_deposit,
+        pool_deposit: pp.pool_deposit,
+        max_epoch: pp.max_epoch,
+        n_opt: pp.n_opt,
+        pool_pledge_influence: pp.pool_pledge_influence.into(),
+        expansion_rate: pp.expansion_rate.into(),
+        treasury_growth_rate: pp.treasury_growth_rate.into(),
+        d: pp.d.into(),
+        extra_entropy,
+        protocol_version: pp.protocol_version.into(),
+        min_pool_cost: pp.min_pool_cost,
+        ada_per_utxo_byte: pp.ada_per_utxo_byte,
+        cost_models,
+        execution_costs: pp.execution_costs.into(),
+        max_tx_ex_units: pp.max_tx_ex_units.into(),
+        max_block_ex_units: pp.max_block_ex_units.into(),
+        max_value_size: pp.max_value_size,
+      })
+  }
+}
+
+impl TryFrom<RProtocolParameters> for ProtocolParameters {
+  type Error = CError;
+
+  fn try_from(pp: RProtocolParameters) -> Result<Self> {
+    pp.cost_models.clone().try_into().map(|cost_models| Self {
+      minfee_a: pp.minfee_a,
+      minfee_b: pp.minfee_b,
+      max_block_body_size: pp.max_block_body_size,
+      max_tx_size: pp.max_tx_size,
+      max_block_header_size: pp.max_block_header_size,
+      key_deposit: pp.key_deposit,
+      pool_deposit: pp.pool_deposit,
+      max_epoch: pp.max_epoch,
+      n_opt: pp.n_opt,
+      pool_pledge_influence: pp.pool_pledge_influence.into(),
+      expansion_rate: pp.expansion_rate.into(),
+      treasury_growth_rate: pp.treasury_growth_rate.into(),
+      d: pp.d.into(),
+      extra_entropy: pp.extra_entropy.into(),
+      protocol_version: pp.protocol_version.into(),
+      min_pool_cost: pp.min_pool_cost,
+      ada_per_utxo_byte: pp.ada_per_utxo_byte,
+      cost_models,
+      execution_costs: pp.execution_costs.into(),
+      max_tx_ex_units: pp.max_tx_ex_units.into(),
+      max_block_ex_units: pp.max_block_ex_units.into(),
+      max_value_size: pp.max_value_size,
+    })
+  }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ProtocolParamField {
+  MinfeeA,
+  MinfeeB,
+  MaxBlockBodySize,
+  MaxTxSize,
+  MaxBlockHeaderSize,
+  KeyDeposit,
+  PoolDeposit,
+  MaxEpoch,
+  NOpt,
+  PoolPledgeInfluence,
+  ExpansionRate,
+  TreasuryGrowthRate,
+  D,
+  ExtraEntropy,
+  ProtocolVersion,
+  MinPoolCost,
+  AdaPerUtxoByte,
+  CostModels,
+  ExecutionCosts,
+  MaxTxExUnits,
+  MaxBlockExUnits,
+  MaxValueSize,
+}
+
+impl Free for ProtocolParamField {
+  unsafe fn free(&mut self) {}
+}
+
+pub type ProtocolParamFields = CArray<ProtocolParamField>;
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_protocol_param_fields_free(
+  protocol_param_fields: &mut ProtocolParamFields,
+) {
+  protocol_param_fields.free()
+}
+
+fn merge_field<T: PartialEq>(
+  current: &mut T, proposed: Option<T>, field: ProtocolParamField,
+  changed: &mut Vec<ProtocolParamField>,
+) {
+  if let Some(proposed) = proposed {
+    if *current != proposed {
+      *current = proposed;
+      changed.push(field);
+    }
+  }
+}
+
+// Same rule as the ledger's `pvCanFollow`: either the minor version is bumped
+// or the major version is bumped and the minor one is reset.
+fn can_follow(current: &RProtocolVersion, next: &RProtocolVersion) -> bool {
+  (next.major() == current.major() && current.minor().checked_add(1) == Some(next.minor()))
+    || (next.major() == current.major() + 1 && next.minor() == 0)
+}
+
+fn check_non_zero(value: u32, name: &str) -> Result<()> {
+  if value == 0 {
+    Err(format!("{} must be greater than zero", name).into())
+  } else {
+    Ok(())
+  }
+}
+
+fn check_unit_interval(value: &RUnitInterval, name: &str) -> Result<()> {
+  let numerator = from_bignum(&value.numerator());
+  let denominator = from_bignum(&value.denominator());
+  if denominator == 0 {
+    Err(format!("{} has zero denominator", name).into())
+  } else if numerator > denominator {
+    Err(format!("{} must be within [0, 1]", name).into())
+  } else {
+    Ok(())
+  }
+}
+
+fn check_ex_units(tx: &RExUnits, block: &RExUnits) -> Result<()> {
+  let (tx_mem, tx_steps) = (from_bignum(&tx.mem()), from_bignum(&tx.steps()));
+  let (block_mem, block_steps) = (from_bignum(&block.mem()), from_bignum(&block.steps()));
+  if tx_mem > block_mem || tx_steps > block_steps {
+    Err("max_tx_ex_units exceeds max_block_ex_units".into())
+  } else {
+    Ok(())
+  }
+}
+
+impl RProtocolParameters {
+  fn validate(&self) -> Result<()> {
+    check_non_zero(self.max_block_body_size, "max_block_body_size")?;
+    check_non_zero(self.max_tx_size, "max_tx_size")?;
+    check_non_zero(self.max_block_header_size, "max_block_header_size")?;
+    check_non_zero(self.max_value_size, "max_value_size")?;
+    check_non_zero(self.n_opt, "n_opt")?;
+    if self.max_tx_size > self.max_block_body_size {
+      return Err("max_tx_size exceeds max_block_body_size".into());
+    }
+    if from_bignum(&self.pool_pledge_influence.denominator()) == 0 {
+      return Err("pool_pledge_influence has zero denominator".into());
+    }
+    check_unit_interval(&self.expansion_rate, "expansion_rate")?;
+    check_unit_interval(&self.treasury_growth_rate, "treasury_growth_rate")?;
+    check_unit_interval(&self.d, "d")?;
+    if from_bignum(&self.execution_costs.mem_price().denominator()) == 0
+      || from_bignum(&self.execution_costs.step_price().denominator()) == 0
+    {
+      return Err("execution_costs has zero denominator".into());
+    }
+    check_ex_units(&self.max_tx_ex_units, &self.max_block_ex_units)
+  }
+
+  fn apply(&mut self, ppu: &RProtocolParamUpdate) -> Result<Vec<ProtocolParamField>> {
+    let coin = |value: Option<RBigNum>| value.map(|value| from_bignum(&value));
+    let protocol_version = match ppu.protocol_version() {
+      None => None,
+      Some(versions) if versions.len() == 1 => Some(versions.get(0)),
+      Some(versions) => {
+        return Err(format!("Expected one protocol version, got {}", versions.len()).into())
+      }
+    };
+    if let Some(protocol_version) = &protocol_version {
+      if *protocol_version != self.protocol_version
+        && !can_follow(&self.protocol_version, protocol_version)
+      {
+        return Err(
+          format!(
+            "Protocol version {}.{} cannot follow {}.{}",
+            protocol_version.major(),
+            protocol_version.minor(),
+            self.protocol_version.major(),
+            self.protocol_version.minor()
+          )
+          .into(),
+        );
+      }
+    }
+    let mut changed = Vec::new();
+    let c = &mut changed;
+    merge_field(&mut self.minfee_a, coin(ppu.minfee_a()), ProtocolParamField::MinfeeA, c);
+    merge_field(&mut self.minfee_b, coin(ppu.minfee_b()), ProtocolParamField::MinfeeB, c);
+    merge_field(
+      &mut self.max_block_body_size,
+      ppu.max_block_body_size(),
+      ProtocolParamField::MaxBlockBodySize,
+      c,
+    );
+    merge_field(&mut self.max_tx_size, ppu.max_tx_size(), ProtocolParamField::MaxTxSize, c);
+    merge_field(
+      &mut self.max_block_header_size,
+      ppu.max_block_header_size(),
+      ProtocolParamField::MaxBlockHeaderSize,
+      c,
+    );
+    merge_field(&mut self.key_deposit, coin(ppu.key_deposit()), ProtocolParamField::KeyDeposit, c);
+    merge_field(
+      &mut self.pool_deposit,
+      coin(ppu.pool_deposit()),
+      ProtocolParamField::PoolDeposit,
+      c,
+    );
+    merge_field(&mut self.max_epoch, ppu.max_epoch(), ProtocolParamField::MaxEpoch, c);
+    merge_field(&mut self.n_opt, ppu.n_opt(), ProtocolParamField::NOpt, c);
+    merge_field(
+      &mut self.pool_pledge_influence,
+      ppu.pool_pledge_influence(),
+      ProtocolParamField::PoolPledgeInfluence,
+      c,
+    );
+    merge_field(
+      &mut self.expansion_rate,
+      ppu.expansion_rate(),
+      ProtocolParamField::ExpansionRate,
+      c,
+    );
+    merge_field(
+      &mut self.treasury_growth_rate,
+      ppu.treasury_growth_rate(),
+      ProtocolParamField::TreasuryGrowthRate,
+      c,
+    );
+    merge_field(&mut self.d, ppu.d(), ProtocolParamField::D, c);
+    merge_field(
+      &mut self.extra_entropy,
+      ppu.extra_entropy(),
+      ProtocolParamField::ExtraEntropy,
+      c,
+    );
+    merge_field(
+      &mut self.protocol_version,
+      protocol_version,
+      ProtocolParamField::ProtocolVersion,
+      c,
+    );
+    merge_field(
+      &mut self.min_pool_cost,
+      coin(ppu.min_pool_cost()),
+      ProtocolParamField::MinPoolCost,
+      c,
+    );
+    merge_field(
+      &mut self.ada_per_utxo_byte,
+      coin(ppu.ada_per_utxo_byte()),
+      ProtocolParamField::AdaPerUtxoByte,
+      c,
+    );
+    merge_field(&mut self.cost_models, ppu.cost_models(), ProtocolParamField::CostModels, c);
+    merge_field(
+      &mut self.execution_costs,
+      ppu.execution_costs(),
+      ProtocolParamField::ExecutionCosts,
+      c,
+    );
+    merge_field(
+      &mut self.max_tx_ex_units,
+      ppu.max_tx_ex_units(),
+      ProtocolParamField::MaxTxExUnits,
+      c,
+    );
+    merge_field(
+      &mut self.max_block_ex_units,
+      ppu.max_block_ex_units(),
+      ProtocolParamField::MaxBlockExUnits,
+      c,
+    );
+    merge_field(
+      &mut self.max_value_size,
+      ppu.max_value_size(),
+      ProtocolParamField::MaxValueSize,
+      c,
+    );
+    self.validate().map(|_| changed)
+  }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct ProtocolParametersChange {
+  parameters: ProtocolParameters,
+  changed: ProtocolParamFields,
+}
+
+impl Free for ProtocolParametersChange {
+  unsafe fn free(&mut self) {
+    self.parameters.free();
+    self.changed.free();
+  }
+}
+
+fn apply_update(
+  params: ProtocolParameters, update: RProtocolParamUpdate,
+) -> Result<ProtocolParametersChange> {
+  params
+    .try_into()
+    .and_then(|mut params: RProtocolParameters| {
+      params
+        .apply(&update)
+        .zip(params.clone().try_into())
+    })
+    .map(|(changed, parameters)| ProtocolParametersChange {
+      parameters,
+      changed: changed.into(),
+    })
+}
+
+pub type GenesisProtocolParametersChangesKeyValue =
+  CKeyValue<GenesisHash, ProtocolParametersChange>;
+pub type GenesisProtocolParametersChanges = CArray<GenesisProtocolParametersChangesKeyValue>;
+
+// Genesis keys may propose different updates, so each proposal is applied on
+// its own and reported under the key that made it.
+fn apply_proposals(
+  params: ProtocolParameters, pppu: RProposedProtocolParameterUpdates,
+) -> Result<GenesisProtocolParametersChanges> {
+  let genesis_hashes = pppu.keys();
+  (0..genesis_hashes.len())
+    .map(|index| {
+      let genesis_hash = genesis_hashes.get(index);
+      let proposal = pppu
+        .get(&genesis_hash)
+        .ok_or_else(|| CError::from("Cannot get ProtocolParamUpdate by GenesisHash"))?;
+      genesis_hash
+        .try_into()
+        .zip(apply_update(params, proposal))
+        .map(|change: (GenesisHash, ProtocolParametersChange)| change.into())
+    })
+    .collect::<Result<Vec<GenesisProtocolParametersChangesKeyValue>>>()
+    .map(|changes| changes.into())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_protocol_parameters_apply_update(
+  params: ProtocolParameters, update: ProtocolParamUpdate,
+  result: &mut ProtocolParametersChange, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    update
+      .try_into()
+      .and_then(|update: RProtocolParamUpdate| apply_update(params, update))
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_protocol_parameters_apply_proposed_updates(
+  params: ProtocolParameters, updates: ProposedProtocolParameterUpdates,
+  result: &mut GenesisProtocolParametersChanges, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    updates.try_into().and_then(|updates| apply_proposals(params, updates))
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_protocol_parameters_validate(
+  params: ProtocolParameters, result: &mut bool, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    params
+      .try_into()
+      .and_then(|params: RProtocolParameters| params.validate())
+      .map(|_| true)
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_protocol_parameters_clone(
+  params: ProtocolParameters, result: &mut ProtocolParameters, error: &mut CError,
+) -> bool {
+  handle_exception(|| params).response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_protocol_parameters_free(params: &mut ProtocolParameters) {
+  params.free()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_protocol_parameters_change_free(
+  change: &mut ProtocolParametersChange,
+) {
+  change.free()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_genesis_protocol_parameters_changes_free(
+  changes: &mut GenesisProtocolParametersChanges,
+) {
+  changes.free()
+}
@@ -1,11 +1,15 @@
+use super::cip5::{self, PublicKeyPrefix};
 use super::data::CData;
+use super::ed25519_signature::Ed25519Signature;
 use super::error::CError;
 use super::panic::*;
 use super::ptr::*;
 use super::stake_credential::Ed25519KeyHash;
 use super::string::CharPtr;
 use super::string::IntoCString;
-use cardano_serialization_lib::crypto::PublicKey as RPublicKey;
+use cardano_serialization_lib::crypto::{
+  Ed25519Signature as REd25519Signature, PublicKey as RPublicKey,
+};
 use std::convert::{TryFrom, TryInto};
 
 pub const PUBLIC_KEY_LENGTH: usize = 32;
@@ -50,6 +54,30 @@ pub unsafe extern "C" fn cardano_public_key_to_bech32(
   }).response(result, error)
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn cardano_public_key_to_cip5_bech32(
+  public_key: PublicKey, prefix: PublicKeyPrefix, result: &mut CharPtr, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    cip5::encode(prefix.hrp(), &public_key.0).map(|bech32| bech32.into_cstr())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_public_key_from_cip5_bech32(
+  bech32: CharPtr, prefix: PublicKeyPrefix, result: &mut PublicKey, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    bech32
+      .unowned()
+      .and_then(|bech32| cip5::decode(prefix.hrp(), bech32))
+      .and_then(|bytes| RPublicKey::from_bytes(&bytes).into_result())
+      .map(|public_key| public_key.into())
+  })
+  .response(result, error)
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn cardano_public_key_from_bytes(
   data: CData, result: &mut PublicKey, error: &mut CError
@@ -82,3 +110,19 @@ pub unsafe extern "C" fn cardano_public_key_hash(
       .and_then(|public_key: RPublicKey| public_key.hash().try_into())
   }).response(result, error)
 }
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_public_key_verify(
+  public_key: PublicKey, message: CData, signature: Ed25519Signature, result: &mut bool,
+  error: &mut CError
+) -> bool {
+  handle_exception_result(|| {
+    public_key
+      .try_into()
+      .zip(message.unowned())
+      .zip(signature.try_into())
+      .map(|((public_key, message), signature): ((RPublicKey, _), REd25519Signature)| {
+        public_key.verify(message, &signature)
+      })
+  }).response(result, error)
+}
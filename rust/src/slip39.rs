@@ -0,0 +1,661 @@
+use crate::array::CArray;
+use crate::bip32_private_key::Bip32PrivateKey;
+use crate::data::CData;
+use crate::error::CError;
+use crate::keystore::random_bytes;
+use crate::panic::*;
+use crate::ptr::*;
+use crate::string::{CharPtr, IntoCString};
+use cardano_serialization_lib::crypto::Bip32PrivateKey as RBip32PrivateKey;
+use cryptoxide::hmac::Hmac;
+use cryptoxide::mac::Mac;
+use cryptoxide::pbkdf2::pbkdf2;
+use cryptoxide::sha2::Sha256;
+use std::collections::BTreeMap;
+use zeroize::Zeroizing;
+
+const RADIX_BITS: usize = 10;
+const HEADER_WORDS: usize = 4;
+const CHECKSUM_WORDS: usize = 3;
+const MIN_SECRET_LENGTH: usize = 16;
+const MIN_MNEMONIC_WORDS: usize =
+  HEADER_WORDS + CHECKSUM_WORDS + (MIN_SECRET_LENGTH * 8).div_ceil(RADIX_BITS);
+const MAX_SHARE_COUNT: u8 = 16;
+const MAX_ITERATION_EXPONENT: u8 = 15;
+const BASE_ITERATION_COUNT: u32 = 10_000;
+const ROUND_COUNT: u8 = 4;
+const DIGEST_LENGTH: usize = 4;
+const DIGEST_INDEX: u8 = 254;
+const SECRET_INDEX: u8 = 255;
+const CUSTOMIZATION: &[u8] = b"shamir";
+const CUSTOMIZATION_EXTENDABLE: &[u8] = b"shamir_extendable";
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct Slip39Group {
+  member_threshold: u8,
+  member_count: u8,
+}
+
+impl Free for Slip39Group {
+  unsafe fn free(&mut self) {}
+}
+
+// Mnemonics of every group, in group order.
+pub type Slip39Shares = CArray<CArray<CharPtr>>;
+
+type Share = (u8, Zeroizing<Vec<u8>>);
+
+// Member threshold and member values by member index.
+type MemberShares<'a> = (u8, BTreeMap<u8, &'a Zeroizing<Vec<u8>>>);
+
+struct ShareData {
+  identifier: u16,
+  extendable: bool,
+  iteration_exponent: u8,
+  group_index: u8,
+  group_threshold: u8,
+  group_count: u8,
+  member_index: u8,
+  member_threshold: u8,
+  value: Zeroizing<Vec<u8>>,
+}
+
+// GF(256) with the Rijndael polynomial x^8 + x^4 + x^3 + x + 1, generator x + 1.
+fn gf_tables() -> ([u8; 255], [u8; 256]) {
+  let mut exp = [0u8; 255];
+  let mut log = [0u8; 256];
+  let mut poly: u16 = 1;
+  for (i, e) in exp.iter_mut().enumerate() {
+    *e = poly as u8;
+    log[poly as usize] = i as u8;
+    poly ^= poly << 1;
+    if poly & 0x100 != 0 {
+      poly ^= 0x11b;
+    }
+  }
+  (exp, log)
+}
+
+fn interpolate(shares: &[Share], x: u8) -> Zeroizing<Vec<u8>> {
+  if let Some((_, value)) = shares.iter().find(|(index, _)| *index == x) {
+    return value.clone();
+  }
+  let (exp, log) = gf_tables();
+  let log_of = |value: u8| log[value as usize] as i64;
+  let log_product: i64 = shares.iter().map(|(index, _)| log_of(index ^ x)).sum();
+  let mut result = Zeroizing::new(vec![0u8; shares[0].1.len()]);
+  for (index, value) in shares {
+    let others: i64 = shares.iter().map(|(other, _)| log_of(index ^ other)).sum();
+    let basis = (log_product - log_of(index ^ x) - others).rem_euclid(255);
+    for (out, byte) in result.iter_mut().zip(value.iter()) {
+      if *byte != 0 {
+        *out ^= exp[((log_of(*byte) + basis) % 255) as usize];
+      }
+    }
+  }
+  result
+}
+
+fn hmac_digest(key: &[u8], secret: &[u8]) -> [u8; DIGEST_LENGTH] {
+  let mut mac = Hmac::new(Sha256::new(), key);
+  mac.input(secret);
+  let mut digest = [0u8; 32];
+  mac.raw_result(&mut digest);
+  let mut result = [0u8; DIGEST_LENGTH];
+  result.copy_from_slice(&digest[..DIGEST_LENGTH]);
+  result
+}
+
+fn split_secret(threshold: u8, count: u8, secret: &[u8]) -> Result<Vec<Share>> {
+  if threshold == 1 {
+    return Ok((0..count).map(|index| (index, Zeroizing::new(secret.to_vec()))).collect());
+  }
+  let random_count = threshold - 2;
+  let mut shares = (0..random_count)
+    .map(|index| random_bytes(secret.len()).map(|value| (index, Zeroizing::new(value))))
+    .collect::<Result<Vec<Share>>>()?;
+  let random_part = Zeroizing::new(random_bytes(secret.len() - DIGEST_LENGTH)?);
+  let mut digest = Zeroizing::new(hmac_digest(&random_part, secret).to_vec());
+  digest.extend_from_slice(&random_part);
+  let mut base = shares.clone();
+  base.push((DIGEST_INDEX, digest));
+  base.push((SECRET_INDEX, Zeroizing::new(secret.to_vec())));
+  for index in random_count..count {
+    shares.push((index, interpolate(&base, index)));
+  }
+  Ok(shares)
+}
+
+fn recover_secret(threshold: u8, shares: &[Share]) -> Result<Zeroizing<Vec<u8>>> {
+  if threshold == 1 {
+    return Ok(shares[0].1.clone());
+  }
+  let secret = interpolate(shares, SECRET_INDEX);
+  let digest = interpolate(shares, DIGEST_INDEX);
+  if digest[..DIGEST_LENGTH] != hmac_digest(&digest[DIGEST_LENGTH..], &secret) {
+    return Err("Invalid digest of the shared secret".into());
+  }
+  Ok(secret)
+}
+
+fn salt(identifier: u16, extendable: bool) -> Vec<u8> {
+  if extendable {
+    Vec::new()
+  } else {
+    let mut salt = CUSTOMIZATION.to_vec();
+    salt.extend_from_slice(&identifier.to_be_bytes());
+    salt
+  }
+}
+
+// Four round Feistel network with PBKDF2-HMAC-SHA256 as the round function.
+fn feistel(
+  data: &[u8], passphrase: &[u8], exponent: u8, salt: &[u8], rounds: &[u8],
+) -> Zeroizing<Vec<u8>> {
+  let half = data.len() / 2;
+  let mut left = Zeroizing::new(data[..half].to_vec());
+  let mut right = Zeroizing::new(data[half..].to_vec());
+  let iterations = (BASE_ITERATION_COUNT << exponent) / ROUND_COUNT as u32;
+  for round in rounds {
+    let mut password = Zeroizing::new(vec![*round]);
+    password.extend_from_slice(passphrase);
+    let mut salted = salt.to_vec();
+    salted.extend_from_slice(&right);
+    let mut mac = Hmac::new(Sha256::new(), &password);
+    let mut f = Zeroizing::new(vec![0u8; right.len()]);
+    pbkdf2(&mut mac, &salted, iterations, &mut f);
+    let next = Zeroizing::new(left.iter().zip(f.iter()).map(|(l, f)| l ^ f).collect());
+    left = std::mem::replace(&mut right, next);
+  }
+  right.extend_from_slice(&left);
+  right
+}
+
+fn check_passphrase(passphrase: &[u8]) -> Result<()> {
+  if passphrase.iter().all(|c| (32..=126).contains(c)) {
+    Ok(())
+  } else {
+    Err("The passphrase must contain only printable ASCII characters".into())
+  }
+}
+
+fn rs1024_polymod(values: impl Iterator<Item = u32>) -> u32 {
+  const GEN: [u32; 10] = [
+    0xE0E040, 0x1C1C080, 0x3838100, 0x7070200, 0xE0E0009, 0x1C0C2412, 0x38086C24, 0x3090FC48,
+    0x21B1F890, 0x3F3F120,
+  ];
+  values.fold(1, |chk, value| {
+    let b = chk >> 20;
+    let chk = (chk & 0xFFFFF) << 10 ^ value;
+    GEN.iter().enumerate().filter(|(i, _)| (b >> i) & 1 != 0).fold(chk, |chk, (_, g)| chk ^ g)
+  })
+}
+
+fn customized(extendable: bool, words: &[u16]) -> Vec<u32> {
+  let customization = if extendable { CUSTOMIZATION_EXTENDABLE } else { CUSTOMIZATION };
+  customization.iter().map(|c| *c as u32).chain(words.iter().map(|w| *w as u32)).collect()
+}
+
+fn bytes_to_words(bytes: &[u8], word_count: usize) -> Vec<u16> {
+  let padding = word_count * RADIX_BITS - bytes.len() * 8;
+  let bit = |i: usize| i >= padding && (bytes[(i - padding) / 8] >> (7 - (i - padding) % 8)) & 1 == 1;
+  (0..word_count)
+    .map(|w| (0..RADIX_BITS).fold(0u16, |word, b| word << 1 | bit(w * RADIX_BITS + b) as u16))
+    .collect()
+}
+
+fn words_to_bytes(words: &[u16], byte_count: usize) -> Result<Zeroizing<Vec<u8>>> {
+  let padding = words.len() * RADIX_BITS - byte_count * 8;
+  let bit = |i: usize| (words[i / RADIX_BITS] >> (RADIX_BITS - 1 - i % RADIX_BITS)) & 1;
+  if (0..padding).any(|i| bit(i) != 0) {
+    return Err("Invalid mnemonic padding".into());
+  }
+  Ok(Zeroizing::new(
+    (0..byte_count)
+      .map(|b| (0..8).fold(0u8, |byte, i| byte << 1 | bit(padding + b * 8 + i) as u8))
+      .collect(),
+  ))
+}
+
+impl ShareData {
+  fn to_mnemonic(&self) -> String {
+    let header = (self.identifier as u64) << 25
+      | (self.extendable as u64) << 24
+      | (self.iteration_exponent as u64) << 20
+      | (self.group_index as u64) << 16
+      | ((self.group_threshold - 1) as u64) << 12
+      | ((self.group_count - 1) as u64) << 8
+      | (self.member_index as u64) << 4
+      | (self.member_threshold - 1) as u64;
+    let mut words: Vec<u16> =
+      (0..HEADER_WORDS).rev().map(|i| (header >> (i * RADIX_BITS) & 0x3FF) as u16).collect();
+    let value_words = (self.value.len() * 8).div_ceil(RADIX_BITS);
+    words.extend(bytes_to_words(&self.value, value_words));
+    let mut values = customized(self.extendable, &words);
+    values.extend_from_slice(&[0; CHECKSUM_WORDS]);
+    let checksum = rs1024_polymod(values.into_iter()) ^ 1;
+    words.extend((0..CHECKSUM_WORDS).rev().map(|i| (checksum >> (i * RADIX_BITS) & 0x3FF) as u16));
+    words.iter().map(|w| WORDLIST[*w as usize]).collect::<Vec<&str>>().join(" ")
+  }
+
+  fn from_mnemonic(mnemonic: &str) -> Result<Self> {
+    let words = mnemonic
+      .split_whitespace()
+      .map(|word| {
+        WORDLIST
+          .binary_search(&word.to_lowercase().as_str())
+          .map(|index| index as u16)
+          .map_err(|_| format!("Invalid mnemonic word \"{}\"", word).into())
+      })
+      .collect::<Result<Vec<u16>>>()?;
+    if words.len() < MIN_MNEMONIC_WORDS {
+      return Err(format!("Invalid mnemonic length {}", words.len()).into());
+    }
+    let value_words = &words[HEADER_WORDS..words.len() - CHECKSUM_WORDS];
+    let padding = value_words.len() * RADIX_BITS % 16;
+    if padding > 8 {
+      return Err(format!("Invalid mnemonic length {}", words.len()).into());
+    }
+    let header = words[..HEADER_WORDS].iter().fold(0u64, |h, w| h << RADIX_BITS | *w as u64);
+    let extendable = header >> 24 & 1 == 1;
+    if rs1024_polymod(customized(extendable, &words).into_iter()) != 1 {
+      return Err("Invalid mnemonic checksum".into());
+    }
+    let share = Self {
+      identifier: (header >> 25) as u16,
+      extendable,
+      iteration_exponent: (header >> 20 & 0xF) as u8,
+      group_index: (header >> 16 & 0xF) as u8,
+      group_threshold: (header >> 12 & 0xF) as u8 + 1,
+      group_count: (header >> 8 & 0xF) as u8 + 1,
+      member_index: (header >> 4 & 0xF) as u8,
+      member_threshold: (header & 0xF) as u8 + 1,
+      value: words_to_bytes(value_words, (value_words.len() * RADIX_BITS - padding) / 8)?,
+    };
+    if share.group_threshold > share.group_count {
+      return Err("Invalid mnemonic, group threshold exceeds the group count".into());
+    }
+    Ok(share)
+  }
+
+  fn same_set(&self, other: &Self) -> bool {
+    self.identifier == other.identifier
+      && self.extendable == other.extendable
+      && self.iteration_exponent == other.iteration_exponent
+      && self.group_threshold == other.group_threshold
+      && self.group_count == other.group_count
+      && self.value.len() == other.value.len()
+  }
+}
+
+fn generate(
+  secret: &[u8], passphrase: &[u8], group_threshold: u8, groups: &[Slip39Group],
+  iteration_exponent: u8, extendable: bool,
+) -> Result<Vec<Vec<String>>> {
+  if secret.len() < MIN_SECRET_LENGTH || secret.len() & 1 == 1 {
+    return Err(format!("Invalid master secret length {}", secret.len()).into());
+  }
+  check_passphrase(passphrase)?;
+  if iteration_exponent > MAX_ITERATION_EXPONENT {
+    return Err(format!("Iteration exponent {} is out of range", iteration_exponent).into());
+  }
+  if groups.is_empty() || groups.len() > MAX_SHARE_COUNT as usize {
+    return Err(format!("Invalid group count {}", groups.len()).into());
+  }
+  if group_threshold == 0 || group_threshold as usize > groups.len() {
+    return Err(format!("Invalid group threshold {}", group_threshold).into());
+  }
+  for group in groups {
+    if group.member_threshold == 0
+      || group.member_threshold > group.member_count
+      || group.member_count > MAX_SHARE_COUNT
+    {
+      return Err(format!(
+        "Invalid member threshold {} of {}",
+        group.member_threshold, group.member_count
+      )
+      .into());
+    }
+    if group.member_threshold == 1 && group.member_count > 1 {
+      return Err("Multiple member shares with member threshold 1 are not allowed".into());
+    }
+  }
+
+  let identifier = random_bytes(2).map(|id| u16::from_be_bytes([id[0], id[1]]) & 0x7FFF)?;
+  let encrypted = feistel(
+    secret,
+    passphrase,
+    iteration_exponent,
+    &salt(identifier, extendable),
+    &[0, 1, 2, 3],
+  );
+  let group_shares = split_secret(group_threshold, groups.len() as u8, &encrypted)?;
+  groups
+    .iter()
+    .zip(group_shares)
+    .map(|(group, (group_index, group_secret))| {
+      split_secret(group.member_threshold, group.member_count, &group_secret).map(|members| {
+        members
+          .into_iter()
+          .map(|(member_index, value)| {
+            ShareData {
+              identifier,
+              extendable,
+              iteration_exponent,
+              group_index,
+              group_threshold,
+              group_count: groups.len() as u8,
+              member_index,
+              member_threshold: group.member_threshold,
+              value,
+            }
+            .to_mnemonic()
+          })
+          .collect()
+      })
+    })
+    .collect()
+}
+
+fn recover(mnemonics: &[&str], passphrase: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+  check_passphrase(passphrase)?;
+  let shares = mnemonics
+    .iter()
+    .map(|mnemonic| ShareData::from_mnemonic(mnemonic))
+    .collect::<Result<Vec<ShareData>>>()?;
+  let first = shares.first().ok_or("No mnemonics were provided")?;
+  if !shares.iter().all(|share| share.same_set(first)) {
+    return Err("Mnemonics do not belong to the same share set".into());
+  }
+  let mut groups: BTreeMap<u8, MemberShares> = BTreeMap::new();
+  for share in &shares {
+    let group = groups.entry(share.group_index).or_insert((share.member_threshold, BTreeMap::new()));
+    if group.0 != share.member_threshold {
+      return Err(format!("Group {} has inconsistent member thresholds", share.group_index).into());
+    }
+    match group.1.insert(share.member_index, &share.value) {
+      Some(value) if value != &share.value => {
+        return Err(format!(
+          "Group {} has different shares with member index {}",
+          share.group_index, share.member_index
+        )
+        .into());
+      }
+      _ => {}
+    }
+  }
+  let group_shares = groups
+    .iter()
+    .filter(|(_, (threshold, members))| members.len() >= *threshold as usize)
+    .take(first.group_threshold as usize)
+    .map(|(index, (threshold, members))| {
+      let members: Vec<Share> = members
+        .iter()
+        .take(*threshold as usize)
+        .map(|(member, value)| (*member, (*value).clone()))
+        .collect();
+      recover_secret(*threshold, &members).map(|secret| (*index, secret))
+    })
+    .collect::<Result<Vec<Share>>>()?;
+  if group_shares.len() < first.group_threshold as usize {
+    return Err(format!(
+      "Insufficient mnemonics, {} of {} groups are complete",
+      group_shares.len(),
+      first.group_threshold
+    )
+    .into());
+  }
+  let encrypted = recover_secret(first.group_threshold, &group_shares)?;
+  Ok(feistel(
+    &encrypted,
+    passphrase,
+    first.iteration_exponent,
+    &salt(first.identifier, first.extendable),
+    &[3, 2, 1, 0],
+  ))
+}
+
+unsafe fn mnemonic_list(mnemonics: &CArray<CharPtr>) -> Result<Vec<&str>> {
+  mnemonics.unowned().and_then(|mnemonics| mnemonics.iter().map(|m| m.unowned()).collect())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_slip39_generate(
+  entropy: CData, passphrase: CData, group_threshold: u8, groups: CArray<Slip39Group>,
+  iteration_exponent: u8, extendable: bool, result: &mut Slip39Shares, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    entropy
+      .unowned()
+      .zip(passphrase.unowned())
+      .zip(groups.unowned())
+      .and_then(|((entropy, passphrase), groups)| {
+        generate(entropy, passphrase, group_threshold, groups, iteration_exponent, extendable)
+      })
+      .map(|groups| {
+        groups
+          .into_iter()
+          .map(|group| group.into_iter().map(|m| m.into_cstr()).collect::<Vec<CharPtr>>().into())
+          .collect::<Vec<CArray<CharPtr>>>()
+          .into()
+      })
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_slip39_shares_free(shares: &mut Slip39Shares) {
+  shares.free()
+}
+
+// Copies the master secret into `result`, release it with cardano_data_wipe.
+#[no_mangle]
+pub unsafe extern "C" fn cardano_slip39_recover(
+  mnemonics: CArray<CharPtr>, passphrase: CData, result: &mut CData, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    mnemonic_list(&mnemonics)
+      .zip(passphrase.unowned())
+      .and_then(|(mnemonics, passphrase)| recover(&mnemonics, passphrase))
+      .map(|secret| secret.to_vec().into())
+  })
+  .response(result, error)
+}
+
+// The recovered master secret is used as Icarus entropy, as with
+// cardano_bip32_private_key_from_bip39_entropy.
+#[no_mangle]
+pub unsafe extern "C" fn cardano_bip32_private_key_from_slip39_mnemonics(
+  mnemonics: CArray<CharPtr>, passphrase: CData, password: CData, result: &mut Bip32PrivateKey,
+  error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    mnemonic_list(&mnemonics)
+      .zip(passphrase.unowned())
+      .zip(password.unowned())
+      .and_then(|((mnemonics, passphrase), password)| {
+        recover(&mnemonics, passphrase)
+          .map(|secret| RBip32PrivateKey::from_bip39_entropy(&secret, password).into())
+      })
+  })
+  .response(result, error)
+}
+
+const WORDLIST: [&str; 1024] = [
+  "academic", "acid", "acne", "acquire", "acrobat", "activity", "actress", "adapt", "adequate",
+  "adjust", "admit", "adorn", "adult", "advance", "advocate", "afraid", "again", "agency", "agree",
+  "aide", "aircraft", "airline", "airport", "ajar", "alarm", "album", "alcohol", "alien", "alive",
+  "alpha", "already", "alto", "aluminum", "always", "amazing", "ambition", "amount", "amuse",
+  "analysis", "anatomy", "ancestor", "ancient", "angel", "angry", "animal", "answer", "antenna",
+  "anxiety", "apart", "aquatic", "arcade", "arena", "argue", "armed", "artist", "artwork",
+  "aspect", "auction", "august", "aunt", "average", "aviation", "avoid", "award", "away", "axis",
+  "axle", "beam", "beard", "beaver", "become", "bedroom", "behavior", "being", "believe", "belong",
+  "benefit", "best", "beyond", "bike", "biology", "birthday", "bishop", "black", "blanket",
+  "blessing", "blimp", "blind", "blue", "body", "bolt", "boring", "born", "both", "boundary",
+  "bracelet", "branch", "brave", "breathe", "briefing", "broken", "brother", "browser", "bucket",
+  "budget", "building", "bulb", "bulge", "bumpy", "bundle", "burden", "burning", "busy", "buyer",
+  "cage", "calcium", "camera", "campus", "canyon", "capacity", "capital", "capture", "carbon",
+  "cards", "careful", "cargo", "carpet", "carve", "category", "cause", "ceiling", "center",
+  "ceramic", "champion", "change", "charity", "check", "chemical", "chest", "chew", "chubby",
+  "cinema", "civil", "class", "clay", "cleanup", "client", "climate", "clinic", "clock", "clogs",
+  "closet", "clothes", "club", "cluster", "coal", "coastal", "coding", "column", "company",
+  "corner", "costume", "counter", "course", "cover", "cowboy", "cradle", "craft", "crazy",
+  "credit", "cricket", "criminal", "crisis", "critical", "crowd", "crucial", "crunch", "crush",
+  "crystal", "cubic", "cultural", "curious", "curly", "custody", "cylinder", "daisy", "damage",
+  "dance", "darkness", "database", "daughter", "deadline", "deal", "debris", "debut", "decent",
+  "decision", "declare", "decorate", "decrease", "deliver", "demand", "density", "deny", "depart",
+  "depend", "depict", "deploy", "describe", "desert", "desire", "desktop", "destroy", "detailed",
+  "detect", "device", "devote", "diagnose", "dictate", "diet", "dilemma", "diminish", "dining",
+  "diploma", "disaster", "discuss", "disease", "dish", "dismiss", "display", "distance", "dive",
+  "divorce", "document", "domain", "domestic", "dominant", "dough", "downtown", "dragon",
+  "dramatic", "dream", "dress", "drift", "drink", "drove", "drug", "dryer", "duckling", "duke",
+  "duration", "dwarf", "dynamic", "early", "earth", "easel", "easy", "echo", "eclipse", "ecology",
+  "edge", "editor", "educate", "either", "elbow", "elder", "election", "elegant", "element",
+  "elephant", "elevator", "elite", "else", "email", "emerald", "emission", "emperor", "emphasis",
+  "employer", "empty", "ending", "endless", "endorse", "enemy", "energy", "enforce", "engage",
+  "enjoy", "enlarge", "entrance", "envelope", "envy", "epidemic", "episode", "equation", "equip",
+  "eraser", "erode", "escape", "estate", "estimate", "evaluate", "evening", "evidence", "evil",
+  "evoke", "exact", "example", "exceed", "exchange", "exclude", "excuse", "execute", "exercise",
+  "exhaust", "exotic", "expand", "expect", "explain", "express", "extend", "extra", "eyebrow",
+  "facility", "fact", "failure", "faint", "fake", "false", "family", "famous", "fancy", "fangs",
+  "fantasy", "fatal", "fatigue", "favorite", "fawn", "fiber", "fiction", "filter", "finance",
+  "findings", "finger", "firefly", "firm", "fiscal", "fishing", "fitness", "flame", "flash",
+  "flavor", "flea", "flexible", "flip", "float", "floral", "fluff", "focus", "forbid", "force",
+  "forecast", "forget", "formal", "fortune", "forward", "founder", "fraction", "fragment",
+  "frequent", "freshman", "friar", "fridge", "friendly", "frost", "froth", "frozen", "fumes",
+  "funding", "furl", "fused", "galaxy", "game", "garbage", "garden", "garlic", "gasoline",
+  "gather", "general", "genius", "genre", "genuine", "geology", "gesture", "glad", "glance",
+  "glasses", "glen", "glimpse", "goat", "golden", "graduate", "grant", "grasp", "gravity", "gray",
+  "greatest", "grief", "grill", "grin", "grocery", "gross", "group", "grownup", "grumpy", "guard",
+  "guest", "guilt", "guitar", "gums", "hairy", "hamster", "hand", "hanger", "harvest", "have",
+  "havoc", "hawk", "hazard", "headset", "health", "hearing", "heat", "helpful", "herald", "herd",
+  "hesitate", "hobo", "holiday", "holy", "home", "hormone", "hospital", "hour", "huge", "human",
+  "humidity", "hunting", "husband", "hush", "husky", "hybrid", "idea", "identify", "idle", "image",
+  "impact", "imply", "improve", "impulse", "include", "income", "increase", "index", "indicate",
+  "industry", "infant", "inform", "inherit", "injury", "inmate", "insect", "inside", "install",
+  "intend", "intimate", "invasion", "involve", "iris", "island", "isolate", "item", "ivory",
+  "jacket", "jerky", "jewelry", "join", "judicial", "juice", "jump", "junction", "junior", "junk",
+  "jury", "justice", "kernel", "keyboard", "kidney", "kind", "kitchen", "knife", "knit", "laden",
+  "ladle", "ladybug", "lair", "lamp", "language", "large", "laser", "laundry", "lawsuit", "leader",
+  "leaf", "learn", "leaves", "lecture", "legal", "legend", "legs", "lend", "length", "level",
+  "liberty", "library", "license", "lift", "likely", "lilac", "lily", "lips", "liquid", "listen",
+  "literary", "living", "lizard", "loan", "lobe", "location", "losing", "loud", "loyalty", "luck",
+  "lunar", "lunch", "lungs", "luxury", "lying", "lyrics", "machine", "magazine", "maiden",
+  "mailman", "main", "makeup", "making", "mama", "manager", "mandate", "mansion", "manual",
+  "marathon", "march", "market", "marvel", "mason", "material", "math", "maximum", "mayor",
+  "meaning", "medal", "medical", "member", "memory", "mental", "merchant", "merit", "method",
+  "metric", "midst", "mild", "military", "mineral", "minister", "miracle", "mixed", "mixture",
+  "mobile", "modern", "modify", "moisture", "moment", "morning", "mortgage", "mother", "mountain",
+  "mouse", "move", "much", "mule", "multiple", "muscle", "museum", "music", "mustang", "nail",
+  "national", "necklace", "negative", "nervous", "network", "news", "nuclear", "numb", "numerous",
+  "nylon", "oasis", "obesity", "object", "observe", "obtain", "ocean", "often", "olympic", "omit",
+  "oral", "orange", "orbit", "order", "ordinary", "organize", "ounce", "oven", "overall", "owner",
+  "paces", "pacific", "package", "paid", "painting", "pajamas", "pancake", "pants", "papa",
+  "paper", "parcel", "parking", "party", "patent", "patrol", "payment", "payroll", "peaceful",
+  "peanut", "peasant", "pecan", "penalty", "pencil", "percent", "perfect", "permit", "petition",
+  "phantom", "pharmacy", "photo", "phrase", "physics", "pickup", "picture", "piece", "pile",
+  "pink", "pipeline", "pistol", "pitch", "plains", "plan", "plastic", "platform", "playoff",
+  "pleasure", "plot", "plunge", "practice", "prayer", "preach", "predator", "pregnant", "premium",
+  "prepare", "presence", "prevent", "priest", "primary", "priority", "prisoner", "privacy",
+  "prize", "problem", "process", "profile", "program", "promise", "prospect", "provide", "prune",
+  "public", "pulse", "pumps", "punish", "puny", "pupal", "purchase", "purple", "python",
+  "quantity", "quarter", "quick", "quiet", "race", "racism", "radar", "railroad", "rainbow",
+  "raisin", "random", "ranked", "rapids", "raspy", "reaction", "realize", "rebound", "rebuild",
+  "recall", "receiver", "recover", "regret", "regular", "reject", "relate", "remember", "remind",
+  "remove", "render", "repair", "repeat", "replace", "require", "rescue", "research", "resident",
+  "response", "result", "retailer", "retreat", "reunion", "revenue", "review", "reward", "rhyme",
+  "rhythm", "rich", "rival", "river", "robin", "rocky", "romantic", "romp", "roster", "round",
+  "royal", "ruin", "ruler", "rumor", "sack", "safari", "salary", "salon", "salt", "satisfy",
+  "satoshi", "saver", "says", "scandal", "scared", "scatter", "scene", "scholar", "science",
+  "scout", "scramble", "screw", "script", "scroll", "seafood", "season", "secret", "security",
+  "segment", "senior", "shadow", "shaft", "shame", "shaped", "sharp", "shelter", "sheriff",
+  "short", "should", "shrimp", "sidewalk", "silent", "silver", "similar", "simple", "single",
+  "sister", "skin", "skunk", "slap", "slavery", "sled", "slice", "slim", "slow", "slush", "smart",
+  "smear", "smell", "smirk", "smith", "smoking", "smug", "snake", "snapshot", "sniff", "society",
+  "software", "soldier", "solution", "soul", "source", "space", "spark", "speak", "species",
+  "spelling", "spend", "spew", "spider", "spill", "spine", "spirit", "spit", "spray", "sprinkle",
+  "square", "squeeze", "stadium", "staff", "standard", "starting", "station", "stay", "steady",
+  "step", "stick", "stilt", "story", "strategy", "strike", "style", "subject", "submit", "sugar",
+  "suitable", "sunlight", "superior", "surface", "surprise", "survive", "sweater", "swimming",
+  "swing", "switch", "symbolic", "sympathy", "syndrome", "system", "tackle", "tactics", "tadpole",
+  "talent", "task", "taste", "taught", "taxi", "teacher", "teammate", "teaspoon", "temple",
+  "tenant", "tendency", "tension", "terminal", "testify", "texture", "thank", "that", "theater",
+  "theory", "therapy", "thorn", "threaten", "thumb", "thunder", "ticket", "tidy", "timber",
+  "timely", "ting", "tofu", "together", "tolerate", "total", "toxic", "tracks", "traffic",
+  "training", "transfer", "trash", "traveler", "treat", "trend", "trial", "tricycle", "trip",
+  "triumph", "trouble", "true", "trust", "twice", "twin", "type", "typical", "ugly", "ultimate",
+  "umbrella", "uncover", "undergo", "unfair", "unfold", "unhappy", "union", "universe", "unkind",
+  "unknown", "unusual", "unwrap", "upgrade", "upstairs", "username", "usher", "usual", "valid",
+  "valuable", "vampire", "vanish", "various", "vegan", "velvet", "venture", "verdict", "verify",
+  "very", "veteran", "vexed", "victim", "video", "view", "vintage", "violence", "viral", "visitor",
+  "visual", "vitamins", "vocal", "voice", "volume", "voter", "voting", "walnut", "warmth", "warn",
+  "watch", "wavy", "wealthy", "weapon", "webcam", "welcome", "welfare", "western", "width",
+  "wildlife", "window", "wine", "wireless", "wisdom", "withdraw", "wits", "wolf", "woman", "work",
+  "worthy", "wrap", "wrist", "writing", "wrote", "year", "yelp", "yield", "yoga", "zero",
+];
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn recover_hex(mnemonics: &[&str]) -> std::result::Result<String, String> {
+    recover(mnemonics, b"TREZOR").map(|secret| hex::encode(&*secret)).map_err(|error| match error {
+      CError::Error(message) => unsafe { message.unowned().unwrap().to_string() },
+      error => format!("{:?}", error),
+    })
+  }
+
+  #[test]
+  fn recovers_without_sharing() {
+    let mnemonic = "duckling enlarge academic academic agency result length solution fridge \
+      kidney coal piece deal husband erode duke ajar critical decision keyboard";
+    assert_eq!(recover_hex(&[mnemonic]).unwrap(), "bb54aac4b89dc868ba37d9cc21b2cece");
+  }
+
+  #[test]
+  fn rejects_invalid_checksum() {
+    let mnemonic = "duckling enlarge academic academic agency result length solution fridge \
+      kidney coal piece deal husband erode duke ajar critical decision kidney";
+    assert!(recover_hex(&[mnemonic]).is_err());
+  }
+
+  #[test]
+  fn recovers_two_of_three() {
+    let first = "shadow pistol academic always adequate wildlife fancy gross oasis cylinder \
+      mustang wrist rescue view short owner flip making coding armed";
+    let second = "shadow pistol academic acid actress prayer class unknown daughter sweater \
+      depict flip twice unkind craft early superior advocate guest smoking";
+    assert_eq!(recover_hex(&[first, second]).unwrap(), "b43ceb7e57a0ea8766221624d01b0864");
+    assert!(recover_hex(&[first]).is_err());
+  }
+
+  #[test]
+  fn recovers_256_bit_secret() {
+    let mnemonic = "theory painting academic academic armed sweater year military elder \
+      discuss acne wildlife boring employer fused large satoshi bundle carbon diagnose anatomy \
+      hamster leaves tracks paces beyond phantom capital marvel lips brave detect luck";
+    assert_eq!(
+      recover_hex(&[mnemonic]).unwrap(),
+      "989baf9dcaad5b10ca33dfd8cc75e42477025dce88ae83e75a230086a0e00e92"
+    );
+  }
+
+  #[test]
+  fn rejects_duplicate_member_indices() {
+    let first = "shadow pistol academic always adequate wildlife fancy gross oasis cylinder \
+      mustang wrist rescue view short owner flip making coding armed";
+    let mut share = ShareData::from_mnemonic(first).unwrap();
+    share.value[0] ^= 1;
+    let forged = share.to_mnemonic();
+    assert_eq!(
+      recover_hex(&[first, &forged]).unwrap_err(),
+      "Group 0 has different shares with member index 2"
+    );
+    // Repeating the same mnemonic is not a second share.
+    assert_eq!(
+      recover_hex(&[first, first]).unwrap_err(),
+      "Insufficient mnemonics, 0 of 1 groups are complete"
+    );
+  }
+}
@@ -3,7 +3,9 @@ use super::error::CError;
 use super::panic::*;
 use super::ptr::Ptr;
 use crate::array::CArray;
+use crate::cip5::{self, KeyHashPrefix};
 use crate::ptr::Free;
+use crate::string::{CharPtr, IntoCString};
 use cardano_serialization_lib::address::{StakeCredKind, StakeCredential as RStakeCredential};
 use cardano_serialization_lib::crypto::{
   Ed25519KeyHash as REd25519KeyHash, ScriptHash as RScriptHash,
@@ -160,6 +162,31 @@ pub unsafe extern "C" fn cardano_ed25519_key_hash_to_bytes(
   .response(result, error)
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn cardano_ed25519_key_hash_to_bech32(
+  hash: Ed25519KeyHash, prefix: KeyHashPrefix, result: &mut CharPtr, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    let rhash: REd25519KeyHash = hash.into();
+    cip5::encode(prefix.hrp(), &rhash.to_bytes()).map(|bech32| bech32.into_cstr())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_ed25519_key_hash_from_bech32(
+  bech32: CharPtr, prefix: KeyHashPrefix, result: &mut Ed25519KeyHash, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    bech32
+      .unowned()
+      .and_then(|bech32| cip5::decode(prefix.hrp(), bech32))
+      .and_then(|bytes| REd25519KeyHash::from_bytes(bytes).into_result())
+      .and_then(|hash| hash.try_into())
+  })
+  .response(result, error)
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn cardano_script_hash_from_bytes(
   data: CData, result: &mut ScriptHash, error: &mut CError,
@@ -184,6 +211,31 @@ pub unsafe extern "C" fn cardano_script_hash_to_bytes(
   .response(result, error)
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn cardano_script_hash_to_bech32(
+  hash: ScriptHash, result: &mut CharPtr, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    let rhash: RScriptHash = hash.into();
+    cip5::encode(cip5::SCRIPT, &rhash.to_bytes()).map(|bech32| bech32.into_cstr())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_script_hash_from_bech32(
+  bech32: CharPtr, result: &mut ScriptHash, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    bech32
+      .unowned()
+      .and_then(|bech32| cip5::decode(cip5::SCRIPT, bech32))
+      .and_then(|bytes| RScriptHash::from_bytes(bytes).into_result())
+      .and_then(|hash| hash.try_into())
+  })
+  .response(result, error)
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn cardano_stake_credential_from_bytes(
   data: CData, result: &mut StakeCredential, error: &mut CError,
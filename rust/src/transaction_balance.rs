@@ -0,0 +1,586 @@
+use crate::address::address::Address;
+use crate::error::CError;
+use crate::panic::*;
+use crate::protocol_parameters::{ProtocolParameters, RProtocolParameters};
+use crate::transaction::Transaction;
+use crate::transaction_body::TransactionBody;
+use crate::transaction_metadata::{native_script_signers, RValidityInterval};
+use crate::transaction_unspent_output::TransactionUnspentOutputs;
+use cardano_serialization_lib::{
+  address::{
+    Address as RAddress, BaseAddress as RBaseAddress, ByronAddress as RByronAddress,
+    EnterpriseAddress as REnterpriseAddress, PointerAddress as RPointerAddress,
+  },
+  crypto::{
+    BootstrapWitness as RBootstrapWitness, BootstrapWitnesses as RBootstrapWitnesses,
+    Ed25519KeyHash as REd25519KeyHash, Ed25519Signature as REd25519Signature,
+    PublicKey as RPublicKey, Vkey as RVkey, Vkeywitness as RVkeywitness,
+    Vkeywitnesses as RVkeywitnesses,
+  },
+  fees::{min_fee, LinearFee as RLinearFee},
+  plutus::{
+    Costmdls as RCostmdls, Language as RLanguage, Redeemer as RRedeemer,
+    RedeemerTagKind as RRedeemerTagKind, Redeemers as RRedeemers,
+  },
+  utils::{
+    from_bignum, get_deposit, get_implicit_input, hash_script_data, min_ada_required, to_bignum,
+    TransactionUnspentOutput as RTransactionUnspentOutput,
+    TransactionUnspentOutputs as RTransactionUnspentOutputs, Value as RValue,
+  },
+  CertificateKind as RCertificateKind, MultiAsset as RMultiAsset, NativeScript as RNativeScript,
+  Transaction as RTransaction,
+  TransactionBody as RTransactionBody, TransactionInput as RTransactionInput,
+  TransactionInputs as RTransactionInputs, TransactionOutput as RTransactionOutput,
+  TransactionOutputs as RTransactionOutputs, TransactionWitnessSet as RTransactionWitnessSet,
+};
+use std::collections::BTreeSet;
+use std::convert::{TryFrom, TryInto};
+
+// Fee and change converge in a couple of rounds, the limit only guards
+// against oscillation between "with change" and "without change" bodies.
+const MAX_BALANCE_ITERATIONS: usize = 16;
+
+fn payment_key_hash(address: &RAddress) -> Option<REd25519KeyHash> {
+  RBaseAddress::from_address(address)
+    .map(|base| base.payment_cred())
+    .or_else(|| REnterpriseAddress::from_address(address).map(|ent| ent.payment_cred()))
+    .or_else(|| RPointerAddress::from_address(address).map(|ptr| ptr.payment_cred()))
+    .and_then(|cred| cred.to_keyhash())
+}
+
+fn is_key_locked(address: &RAddress) -> bool {
+  RByronAddress::from_address(address).is_some() || payment_key_hash(address).is_some()
+}
+
+// Key hashes and Byron addresses which have to sign the body. Native scripts
+// contribute their cheapest signer set, preferring keys that already signed,
+// and are returned separately when the body's validity interval or the
+// available keys can't satisfy them.
+pub(crate) fn required_signers(
+  body: &RTransactionBody, witness_set: &RTransactionWitnessSet, spent: &[RTransactionOutput],
+  signed: &BTreeSet<REd25519KeyHash>,
+) -> (BTreeSet<REd25519KeyHash>, Vec<RByronAddress>, Vec<RNativeScript>) {
+  let mut hashes = BTreeSet::new();
+  let mut byrons: Vec<RByronAddress> = Vec::new();
+  for output in spent {
+    let address = output.address();
+    if let Some(hash) = payment_key_hash(&address) {
+      hashes.insert(hash);
+    } else if let Some(byron) = RByronAddress::from_address(&address) {
+      if !byrons.iter().any(|known| known.to_bytes() == byron.to_bytes()) {
+        byrons.push(byron);
+      }
+    }
+  }
+  if let Some(certs) = body.certs() {
+    for i in 0..certs.len() {
+      let cert = certs.get(i);
+      match cert.kind() {
+        RCertificateKind::StakeDeregistration => {
+          cert.as_stake_deregistration().and_then(|cert| cert.stake_credential().to_keyhash())
+        }
+        RCertificateKind::StakeDelegation => {
+          cert.as_stake_delegation().and_then(|cert| cert.stake_credential().to_keyhash())
+        }
+        RCertificateKind::PoolRegistration => cert.as_pool_registration().map(|cert| {
+          let owners = cert.pool_params().pool_owners();
+          for i in 0..owners.len() {
+            hashes.insert(owners.get(i));
+          }
+          cert.pool_params().operator()
+        }),
+        RCertificateKind::PoolRetirement => cert.as_pool_retirement().map(|cert| cert.pool_keyhash()),
+        _ => None,
+      }
+      .map(|hash| hashes.insert(hash));
+    }
+  }
+  if let Some(withdrawals) = body.withdrawals() {
+    let addresses = withdrawals.keys();
+    for i in 0..addresses.len() {
+      if let Some(hash) = addresses.get(i).payment_cred().to_keyhash() {
+        hashes.insert(hash);
+      }
+    }
+  }
+  if let Some(signers) = body.required_signers() {
+    for i in 0..signers.len() {
+      hashes.insert(signers.get(i));
+    }
+  }
+  let mut unsatisfied = Vec::new();
+  if let Some(scripts) = witness_set.native_scripts() {
+    let available: BTreeSet<REd25519KeyHash> = hashes.union(signed).cloned().collect();
+    let interval = RValidityInterval {
+      invalid_before: body.validity_start_interval(),
+      invalid_hereafter: body.ttl(),
+    };
+    for i in 0..scripts.len() {
+      let script = scripts.get(i);
+      match native_script_signers(&script, &available, Some(&interval)) {
+        Some(signers) => hashes.extend(signers),
+        None => unsatisfied.push(script),
+      }
+    }
+  }
+  (hashes, byrons, unsatisfied)
+}
+
+// Required witnesses which are not in the witness set yet.
+pub(crate) fn required_witnesses(
+  body: &RTransactionBody, witness_set: &RTransactionWitnessSet, spent: &[RTransactionOutput],
+) -> (BTreeSet<REd25519KeyHash>, Vec<RByronAddress>) {
+  let mut signed = BTreeSet::new();
+  if let Some(vkeys) = witness_set.vkeys() {
+    for i in 0..vkeys.len() {
+      signed.insert(vkeys.get(i).vkey().public_key().hash());
+    }
+  }
+  let (hashes, byrons, _) = required_signers(body, witness_set, spent, &signed);
+  (hashes.difference(&signed).cloned().collect(), byrons)
+}
+
+// Same shape as the final witness set, signatures are zero-filled.
+fn fake_witness_set(
+  witness_set: &RTransactionWitnessSet, vkeys: usize, byrons: &[RByronAddress],
+) -> Result<RTransactionWitnessSet> {
+  let vkey = RPublicKey::from_bytes(&[0u8; 32]).into_result().map(|key| RVkey::new(&key))?;
+  let signature = REd25519Signature::from_bytes(vec![0u8; 64]).into_result()?;
+  let mut fake = witness_set.clone();
+  let mut vkey_witnesses = witness_set.vkeys().unwrap_or_else(RVkeywitnesses::new);
+  for _ in 0..vkeys {
+    vkey_witnesses.add(&RVkeywitness::new(&vkey, &signature));
+  }
+  if vkey_witnesses.len() > 0 {
+    fake.set_vkeys(&vkey_witnesses);
+  }
+  let mut bootstraps = witness_set.bootstraps().unwrap_or_else(RBootstrapWitnesses::new);
+  for byron in byrons {
+    bootstraps.add(&RBootstrapWitness::new(&vkey, &signature, vec![0u8; 32], byron.attributes()));
+  }
+  if bootstraps.len() > 0 {
+    fake.set_bootstraps(&bootstraps);
+  }
+  Ok(fake)
+}
+
+// Ledger txscriptfee: prices applied to the summed units, rounded up once.
+fn script_fee(witness_set: &RTransactionWitnessSet, params: &RProtocolParameters) -> Result<u64> {
+  let redeemers = match witness_set.redeemers() {
+    Some(redeemers) => redeemers,
+    None => return Ok(0),
+  };
+  let (mut mem, mut steps) = (0u128, 0u128);
+  for i in 0..redeemers.len() {
+    let ex_units = redeemers.get(i).ex_units();
+    mem += from_bignum(&ex_units.mem()) as u128;
+    steps += from_bignum(&ex_units.steps()) as u128;
+  }
+  let mem_price = params.execution_costs.mem_price();
+  let step_price = params.execution_costs.step_price();
+  let (mem_num, mem_den) =
+    (from_bignum(&mem_price.numerator()) as u128, from_bignum(&mem_price.denominator()) as u128);
+  let (step_num, step_den) =
+    (from_bignum(&step_price.numerator()) as u128, from_bignum(&step_price.denominator()) as u128);
+  if mem_den == 0 || step_den == 0 {
+    return Err("Execution unit prices have zero denominator".into());
+  }
+  mem
+    .checked_mul(mem_num * step_den)
+    .zip(steps.checked_mul(step_num * mem_den))
+    .and_then(|(mem, steps)| mem.checked_add(steps))
+    .map(|total| total.div_ceil(mem_den * step_den))
+    .and_then(|fee| u64::try_from(fee).ok())
+    .ok_or_else(|| "Script fee overflow".into())
+}
+
+fn mint_values(body: &RTransactionBody) -> Result<(RValue, RValue)> {
+  let mut minted = RMultiAsset::new();
+  let mut burned = RMultiAsset::new();
+  if let Some(mint) = body.mint() {
+    let policies = mint.keys();
+    for i in 0..policies.len() {
+      let policy = policies.get(i);
+      let assets = match mint.get(&policy) {
+        Some(assets) => assets,
+        None => continue,
+      };
+      let names = assets.keys();
+      for j in 0..names.len() {
+        let name = names.get(j);
+        if let Some(amount) = assets.get(&name) {
+          match (amount.as_positive(), amount.as_negative()) {
+            (Some(amount), _) => minted.set_asset(&policy, &name, amount),
+            (_, Some(amount)) => burned.set_asset(&policy, &name, amount),
+            _ => None,
+          };
+        }
+      }
+    }
+  }
+  let mut minted_value = RValue::zero();
+  let mut burned_value = RValue::zero();
+  if minted.len() > 0 {
+    minted_value.set_multiasset(&minted);
+  }
+  if burned.len() > 0 {
+    burned_value.set_multiasset(&burned);
+  }
+  Ok((minted_value, burned_value))
+}
+
+fn sorted_inputs(inputs: &RTransactionInputs) -> Vec<RTransactionInput> {
+  let mut sorted: Vec<RTransactionInput> = (0..inputs.len()).map(|i| inputs.get(i)).collect();
+  sorted.sort();
+  sorted.dedup();
+  sorted
+}
+
+// Spend redeemers point into the sorted input set, so they have to follow
+// the inputs they were created for once new ones are added.
+fn remap_redeemers(
+  redeemers: &RRedeemers, old_inputs: &[RTransactionInput], new_inputs: &[RTransactionInput],
+) -> Result<RRedeemers> {
+  let mut remapped = RRedeemers::new();
+  for i in 0..redeemers.len() {
+    let redeemer = redeemers.get(i);
+    if redeemer.tag().kind() != RRedeemerTagKind::Spend {
+      remapped.add(&redeemer);
+      continue;
+    }
+    let index = from_bignum(&redeemer.index()) as usize;
+    let input = old_inputs
+      .get(index)
+      .ok_or_else(|| format!("Spend redeemer index {} is out of inputs range", index))?;
+    let new_index = new_inputs.iter().position(|new_input| new_input == input).unwrap();
+    remapped.add(&RRedeemer::new(
+      &redeemer.tag(),
+      &to_bignum(new_index as u64),
+      &redeemer.data(),
+      &redeemer.ex_units(),
+    ));
+  }
+  Ok(remapped)
+}
+
+// The language views cover only the languages the redeemers run. The ledger
+// era supported here has Plutus V1 only, so any redeemer selects it.
+fn used_cost_models(redeemers: &RRedeemers, cost_models: &RCostmdls) -> RCostmdls {
+  let mut used = RCostmdls::new();
+  let language = RLanguage::new_plutus_v1();
+  if redeemers.len() > 0 {
+    if let Some(cost_model) = cost_models.get(&language) {
+      used.insert(&language, &cost_model);
+    }
+  }
+  used
+}
+
+fn rebuild_body(
+  template: &RTransactionBody, inputs: &[RTransactionInput], outputs: &RTransactionOutputs,
+  fee: u64,
+) -> RTransactionBody {
+  let mut tx_inputs = RTransactionInputs::new();
+  for input in inputs {
+    tx_inputs.add(input);
+  }
+  let mut body = RTransactionBody::new(&tx_inputs, outputs, &to_bignum(fee), template.ttl());
+  if let Some(certs) = template.certs() {
+    body.set_certs(&certs);
+  }
+  if let Some(withdrawals) = template.withdrawals() {
+    body.set_withdrawals(&withdrawals);
+  }
+  if let Some(update) = template.update() {
+    body.set_update(&update);
+  }
+  if let Some(auxiliary_data_hash) = template.auxiliary_data_hash() {
+    body.set_auxiliary_data_hash(&auxiliary_data_hash);
+  }
+  if let Some(validity_start_interval) = template.validity_start_interval() {
+    body.set_validity_start_interval(validity_start_interval);
+  }
+  if let Some(mint) = template.mint() {
+    body.set_mint(&mint);
+  }
+  if let Some(script_data_hash) = template.script_data_hash() {
+    body.set_script_data_hash(&script_data_hash);
+  }
+  if let Some(collateral) = template.collateral() {
+    body.set_collateral(&collateral);
+  }
+  if let Some(required_signers) = template.required_signers() {
+    body.set_required_signers(&required_signers);
+  }
+  if let Some(network_id) = template.network_id() {
+    body.set_network_id(&network_id);
+  }
+  body
+}
+
+struct Balancer<'a> {
+  tx: &'a RTransaction,
+  params: &'a RProtocolParameters,
+  change_address: &'a RAddress,
+  utxos: Vec<RTransactionUnspentOutput>,
+  // Outputs of inputs the wallet does not own, e.g. dApp script inputs.
+  foreign_utxos: Vec<RTransactionUnspentOutput>,
+}
+
+impl<'a> Balancer<'a> {
+  fn resolve(&self, input: &RTransactionInput) -> Result<RTransactionOutput> {
+    self
+      .utxos
+      .iter()
+      .chain(self.foreign_utxos.iter())
+      .find(|utxo| &utxo.input() == input)
+      .map(|utxo| utxo.output())
+      .ok_or_else(|| {
+        format!(
+          "Input {}#{} is not in the provided UTxO sets",
+          hex::encode(input.transaction_id().to_bytes()),
+          input.index()
+        )
+        .into()
+      })
+  }
+
+  fn min_change(&self, change: &RValue) -> Result<u64> {
+    min_ada_required(change, false, &to_bignum(self.params.ada_per_utxo_byte))
+      .into_result()
+      .map(|coin| from_bignum(&coin))
+  }
+
+  // Largest holder of every missing asset first, then largest ADA holders
+  // until the requested lovelace amount is reached.
+  fn select(
+    &self, candidates: &mut Vec<RTransactionUnspentOutput>, selected: &mut Vec<RTransactionUnspentOutput>,
+    available: &mut RValue, needed: &RValue, extra_coin: u64,
+  ) -> Result<()> {
+    while let Some(missing) = needed.clamped_sub(available).multiasset().filter(|ma| ma.len() > 0) {
+      let policy = missing.keys().get(0);
+      let name = missing.get(&policy).unwrap().keys().get(0);
+      let best = candidates
+        .iter()
+        .enumerate()
+        .map(|(i, utxo)| {
+          let amount = utxo
+            .output()
+            .amount()
+            .multiasset()
+            .map(|ma| from_bignum(&ma.get_asset(&policy, &name)))
+            .unwrap_or(0);
+          (i, amount)
+        })
+        .filter(|(_, amount)| *amount > 0)
+        .max_by_key(|(_, amount)| *amount)
+        .map(|(i, _)| i)
+        .ok_or_else(|| {
+          format!(
+            "Insufficient input: not enough {}.{}",
+            hex::encode(policy.to_bytes()),
+            hex::encode(name.name())
+          )
+        })?;
+      let utxo = candidates.remove(best);
+      *available = available.checked_add(&utxo.output().amount()).into_result()?;
+      selected.push(utxo);
+    }
+    let target = from_bignum(&needed.coin())
+      .checked_add(extra_coin)
+      .ok_or_else(|| CError::from("Coin overflow"))?;
+    while from_bignum(&available.coin()) < target {
+      let best = candidates
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, utxo)| from_bignum(&utxo.output().amount().coin()))
+        .map(|(i, _)| i)
+        .ok_or_else(|| CError::from("Insufficient input: not enough ADA"))?;
+      let utxo = candidates.remove(best);
+      *available = available.checked_add(&utxo.output().amount()).into_result()?;
+      selected.push(utxo);
+    }
+    Ok(())
+  }
+
+  fn build(
+    &self, inputs: &[RTransactionInput], outputs: &RTransactionOutputs, fee: u64,
+  ) -> Result<RTransaction> {
+    let body = self.tx.body();
+    let old_inputs = sorted_inputs(&body.inputs());
+    let mut witness_set = self.tx.witness_set();
+    let mut new_body = rebuild_body(&body, inputs, outputs, fee);
+    if let Some(redeemers) = witness_set.redeemers() {
+      let remapped = remap_redeemers(&redeemers, &old_inputs, inputs)?;
+      if remapped.to_bytes() != redeemers.to_bytes() || body.script_data_hash().is_none() {
+        new_body.set_script_data_hash(&hash_script_data(
+          &remapped,
+          &used_cost_models(&remapped, &self.params.cost_models),
+          witness_set.plutus_data(),
+        ));
+      }
+      witness_set.set_redeemers(&remapped);
+    }
+    let mut tx = RTransaction::new(&new_body, &witness_set, self.tx.auxiliary_data());
+    tx.set_is_valid(self.tx.is_valid());
+    Ok(tx)
+  }
+
+  fn required_fee(&self, tx: &RTransaction, spent: &[RTransactionOutput]) -> Result<u64> {
+    let (hashes, byrons) = required_witnesses(&tx.body(), &tx.witness_set(), spent);
+    let fake_witnesses = fake_witness_set(&tx.witness_set(), hashes.len(), &byrons)?;
+    let mut fake = RTransaction::new(&tx.body(), &fake_witnesses, tx.auxiliary_data());
+    fake.set_is_valid(tx.is_valid());
+    let linear_fee =
+      RLinearFee::new(&to_bignum(self.params.minfee_a), &to_bignum(self.params.minfee_b));
+    min_fee(&fake, &linear_fee)
+      .into_result()
+      .map(|fee| from_bignum(&fee))
+      .zip(script_fee(&tx.witness_set(), self.params))
+      .and_then(|(size_fee, script_fee)| {
+        size_fee.checked_add(script_fee).ok_or_else(|| "Fee overflow".into())
+      })
+  }
+
+  fn balance(&self) -> Result<RTransaction> {
+    let body = self.tx.body();
+    let own_inputs = sorted_inputs(&body.inputs());
+    let own_spent = own_inputs.iter().map(|input| self.resolve(input)).collect::<Result<Vec<_>>>()?;
+    // Collateral adds no value but its key witnesses count towards the fee.
+    let collateral_spent = body
+      .collateral()
+      .map(|collateral| sorted_inputs(&collateral))
+      .unwrap_or_default()
+      .iter()
+      .map(|input| self.resolve(input))
+      .collect::<Result<Vec<_>>>()?;
+    let pool_deposit = to_bignum(self.params.pool_deposit);
+    let key_deposit = to_bignum(self.params.key_deposit);
+    let (minted, burned) = mint_values(&body)?;
+
+    let mut available = get_implicit_input(&body, &pool_deposit, &key_deposit)
+      .into_result()
+      .and_then(|implicit| implicit.checked_add(&minted).into_result())?;
+    for output in own_spent.iter() {
+      available = available.checked_add(&output.amount()).into_result()?;
+    }
+    let mut spending = get_deposit(&body, &pool_deposit, &key_deposit)
+      .into_result()
+      .map(|deposit| RValue::new(&deposit))
+      .and_then(|deposit| deposit.checked_add(&burned).into_result())?;
+    let outputs = body.outputs();
+    for i in 0..outputs.len() {
+      spending = spending.checked_add(&outputs.get(i).amount()).into_result()?;
+    }
+
+    let mut candidates: Vec<RTransactionUnspentOutput> = self
+      .utxos
+      .iter()
+      .filter(|utxo| !own_inputs.contains(&utxo.input()))
+      .filter(|utxo| is_key_locked(&utxo.output().address()))
+      .cloned()
+      .collect();
+    let mut selected: Vec<RTransactionUnspentOutput> = Vec::new();
+    let mut fee = 0u64;
+
+    for _ in 0..MAX_BALANCE_ITERATIONS {
+      let needed = spending.checked_add(&RValue::new(&to_bignum(fee))).into_result()?;
+      self.select(&mut candidates, &mut selected, &mut available, &needed, 0)?;
+      let mut change = available.checked_sub(&needed).into_result()?;
+      // Inputs selected for the change ADA may bring new assets, which raise
+      // its minimum again.
+      while change.multiasset().is_some() {
+        let min_change = self.min_change(&change)?;
+        if from_bignum(&change.coin()) >= min_change {
+          break;
+        }
+        self.select(&mut candidates, &mut selected, &mut available, &needed, min_change)?;
+        change = available.checked_sub(&needed).into_result()?;
+      }
+
+      let mut inputs = own_inputs.clone();
+      inputs.extend(selected.iter().map(|utxo| utxo.input()));
+      inputs.sort();
+      let mut spent = own_spent.clone();
+      spent.extend(selected.iter().map(|utxo| utxo.output()));
+      spent.extend(collateral_spent.iter().cloned());
+
+      let mut tx_outputs = body.outputs();
+      let change_coin = from_bignum(&change.coin());
+      let tx_fee = if change.multiasset().is_some() || change_coin >= self.min_change(&change)? {
+        let change_output = RTransactionOutput::new(self.change_address, &change);
+        if change.to_bytes().len() > self.params.max_value_size as usize {
+          return Err("Change output exceeds max value size".into());
+        }
+        tx_outputs.add(&change_output);
+        fee
+      } else {
+        fee + change_coin
+      };
+
+      let tx = self.build(&inputs, &tx_outputs, tx_fee)?;
+      let required = self.required_fee(&tx, &spent)?;
+      if tx_fee >= required {
+        if tx.to_bytes().len() > self.params.max_tx_size as usize {
+          return Err("Balanced transaction exceeds max transaction size".into());
+        }
+        return Ok(tx);
+      }
+      fee = required;
+    }
+    Err("Transaction fee did not converge".into())
+  }
+}
+
+fn balance(
+  tx: RTransaction, utxos: RTransactionUnspentOutputs, foreign_utxos: RTransactionUnspentOutputs,
+  change_address: RAddress, params: RProtocolParameters,
+) -> Result<RTransaction> {
+  let utxos = (0..utxos.len()).map(|i| utxos.get(i)).collect();
+  let foreign_utxos = (0..foreign_utxos.len()).map(|i| foreign_utxos.get(i)).collect();
+  Balancer { tx: &tx, params: &params, change_address: &change_address, utxos, foreign_utxos }
+    .balance()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_transaction_balance(
+  transaction: Transaction, utxos: TransactionUnspentOutputs,
+  foreign_utxos: TransactionUnspentOutputs, change_address: Address,
+  params: ProtocolParameters, result: &mut Transaction, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    transaction
+      .try_into()
+      .zip(utxos.try_into())
+      .zip(foreign_utxos.try_into())
+      .zip(change_address.try_into())
+      .zip(params.try_into())
+      .and_then(|((((tx, utxos), foreign_utxos), change_address), params)| {
+        balance(tx, utxos, foreign_utxos, change_address, params)
+      })
+      .and_then(|tx| tx.try_into())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_transaction_body_balance(
+  body: TransactionBody, utxos: TransactionUnspentOutputs,
+  foreign_utxos: TransactionUnspentOutputs, change_address: Address,
+  params: ProtocolParameters, result: &mut TransactionBody, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    body
+      .try_into()
+      .zip(utxos.try_into())
+      .zip(foreign_utxos.try_into())
+      .zip(change_address.try_into())
+      .zip(params.try_into())
+      .and_then(|((((body, utxos), foreign_utxos), change_address), params)| {
+        let tx = RTransaction::new(&body, &RTransactionWitnessSet::new(), None);
+        balance(tx, utxos, foreign_utxos, change_address, params)
+      })
+      .and_then(|tx| tx.body().try_into())
+  })
+  .response(result, error)
+}
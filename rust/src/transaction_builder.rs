@@ -12,7 +12,9 @@ use crate::ptr::*;
 use crate::stake_credential::{Ed25519KeyHash, ScriptHash};
 use crate::transaction_body::{Mint, TransactionBody};
 use crate::transaction_input::TransactionInput;
-use crate::transaction_metadata::{AuxiliaryData, NativeScripts};
+use crate::transaction_metadata::{
+  native_script_signers, AuxiliaryData, NativeScript, NativeScripts, RValidityInterval,
+};
 use crate::transaction_output::{TransactionOutput, TransactionOutputs};
 use crate::transaction_unspent_output::TransactionUnspentOutputs;
 use crate::value::Value;
@@ -31,9 +33,11 @@ use cardano_serialization_lib::{
     from_bignum, to_bignum, BigNum as RBigNum, Coin as RCoin,
     TransactionUnspentOutputs as RTransactionUnspentOutputs, Value as RValue,
   },
-  Certificates as RCertificates, Mint as RMint, NativeScripts as RNativeScripts,
+  Certificates as RCertificates, Mint as RMint, NativeScript as RNativeScript,
+  NativeScripts as RNativeScripts, ScriptHashNamespace as RScriptHashNamespace,
   TransactionInput as RTransactionInput, TransactionOutput as RTransactionOutput,
-  TransactionOutputs as RTransactionOutputs, Withdrawals as RWithdrawals,
+  TransactionOutputs as RTransactionOutputs, TransactionWitnessSet as RTransactionWitnessSet,
+  Withdrawals as RWithdrawals,
 };
 use std::collections::BTreeSet;
 use std::convert::{TryFrom, TryInto};
@@ -296,6 +300,7 @@ pub struct TransactionBuilder {
   input_types: MockWitnessSet,
   mint: COption<Mint>,
   mint_scripts: COption<NativeScripts>,
+  native_scripts: COption<NativeScripts>,
 }
 
 impl Free for TransactionBuilder {
@@ -307,6 +312,80 @@ impl Free for TransactionBuilder {
     self.auxiliary_data.free();
     self.input_types.free();
     self.mint_scripts.free();
+    self.native_scripts.free();
+  }
+}
+
+// CSL's builder keeps no native scripts of script inputs, so they live only on
+// the C side and every call returning a builder copies them over.
+impl TransactionBuilder {
+  fn native_scripts(&self) -> Result<Option<RNativeScripts>> {
+    let native_scripts: Option<NativeScripts> = self.native_scripts.into();
+    native_scripts
+      .map(|native_scripts| native_scripts.try_into())
+      .transpose()
+  }
+
+  fn with_native_scripts(mut self, native_scripts: Option<RNativeScripts>) -> Result<Self> {
+    self.native_scripts = native_scripts
+      .map(|native_scripts| native_scripts.try_into())
+      .transpose()?
+      .into();
+    Ok(self)
+  }
+
+  fn with_native_scripts_of(self, tb: &TransactionBuilder) -> Result<Self> {
+    tb.native_scripts()
+      .and_then(|native_scripts| self.with_native_scripts(native_scripts))
+  }
+
+  // Bytes the input native scripts add to the witness set next to mint_scripts.
+  fn native_scripts_size(&self) -> Result<u64> {
+    let native_scripts = match self.native_scripts()? {
+      Some(native_scripts) => native_scripts,
+      None => return Ok(0),
+    };
+    let mint_scripts: Option<NativeScripts> = self.mint_scripts.into();
+    let mint_scripts: Option<RNativeScripts> = mint_scripts
+      .map(|mint_scripts| mint_scripts.try_into())
+      .transpose()?;
+    let mut scripts = mint_scripts.clone().unwrap_or_else(RNativeScripts::new);
+    add_native_script_once(&mut scripts, &native_scripts);
+    let witness_set_size = |scripts: Option<&RNativeScripts>| {
+      let mut witness_set = RTransactionWitnessSet::new();
+      if let Some(scripts) = scripts {
+        witness_set.set_native_scripts(scripts);
+      }
+      witness_set.to_bytes().len() as u64
+    };
+    Ok(witness_set_size(Some(&scripts)) - witness_set_size(mint_scripts.as_ref()))
+  }
+
+  // The fee estimate only witnesses mint_scripts, so the fee constant pays for
+  // the input native scripts too. Callers restore the config afterwards.
+  fn with_native_scripts_fee(mut self) -> Result<Self> {
+    let size = self.native_scripts_size()?;
+    let fee_algo = &mut self.config.fee_algo;
+    fee_algo.constant = fee_algo
+      .coefficient
+      .checked_mul(size)
+      .and_then(|fee| fee.checked_add(fee_algo.constant))
+      .ok_or_else(|| CError::from("NativeScripts fee overflow"))?;
+    Ok(self)
+  }
+}
+
+// Adds the scripts missing from `scripts`, a script is witnessed once however
+// many inputs or policies it covers.
+fn add_native_script_once(scripts: &mut RNativeScripts, new_scripts: &RNativeScripts) {
+  for i in 0..new_scripts.len() {
+    let new_script = new_scripts.get(i);
+    let hash = new_script.hash(RScriptHashNamespace::NativeScript);
+    let known = (0..scripts.len())
+      .any(|j| scripts.get(j).hash(RScriptHashNamespace::NativeScript) == hash);
+    if !known {
+      scripts.add(&new_script);
+    }
   }
 }
 
@@ -431,6 +510,7 @@ impl TryFrom<TTransactionBuilder> for TransactionBuilder {
           input_types,
           mint: mint.into(),
           mint_scripts: mint_scripts.into(),
+          native_scripts: None.into(),
         },
       )
   }
@@ -474,6 +554,7 @@ pub unsafe extern "C" fn cardano_transaction_builder_add_inputs_from(
         },
       )
       .map(|(_, tb)| tb)
+      .and_then(|result: TransactionBuilder| result.with_native_scripts_of(&tb))
   })
   .response(result, error)
 }
@@ -491,23 +572,86 @@ pub unsafe extern "C" fn cardano_transaction_builder_add_key_input(
         tb
       })
       .and_then(|tb| tb.try_into())
+      .and_then(|result: TransactionBuilder| result.with_native_scripts_of(&tb))
   })
   .response(result, error)
 }
 
+fn validity_interval(tb: &TTransactionBuilder) -> RValidityInterval {
+  RValidityInterval {
+    invalid_before: tb.validity_start_interval,
+    invalid_hereafter: tb.ttl,
+  }
+}
+
+// A native script input counts the script's cheapest signer set into the
+// mock witnesses and keeps the script, so the fee covers its vkey witnesses and
+// the script itself. Timelocks are checked against the builder's bounds if
+// either of them is set, and again by checked_native_scripts.
+fn add_script_input(
+  mut tb: TTransactionBuilder, mut native_scripts: Option<RNativeScripts>, hash: RScriptHash,
+  native_script: Option<RNativeScript>, input: RTransactionInput, amount: RValue,
+) -> Result<(TTransactionBuilder, Option<RNativeScripts>)> {
+  if let Some(native_script) = native_script {
+    if native_script.hash(RScriptHashNamespace::NativeScript) != hash {
+      return Err("NativeScript does not match the script hash".into());
+    }
+    let interval = validity_interval(&tb);
+    let interval = Some(&interval)
+      .filter(|interval| interval.invalid_before.is_some() || interval.invalid_hereafter.is_some());
+    let signers = native_script_signers(&native_script, &tb.input_types.vkeys, interval)
+      .ok_or_else(|| CError::from("NativeScript can't be satisfied"))?;
+    tb.input_types.vkeys.extend(signers);
+    let mut scripts = RNativeScripts::new();
+    scripts.add(&native_script);
+    add_native_script_once(native_scripts.get_or_insert_with(RNativeScripts::new), &scripts);
+  }
+  tb.inputs.push(TTxBuilderInput { input, amount });
+  tb.input_types.scripts.insert(hash);
+  Ok((tb, native_scripts))
+}
+
+// ttl and validity_start_interval may change after a script input is added, so
+// the kept scripts are checked against the bounds the builder has now, and the
+// signers those bounds need are counted into the mock witnesses. A built body
+// has its bounds final, so missing ones fail timelocks instead of skipping them.
+fn checked_native_scripts(tb: TransactionBuilder, built: bool) -> Result<RTransactionBuilder> {
+  tb.native_scripts()
+    .zip(tb.with_native_scripts_fee().and_then(|tb| tb.try_into()))
+    .and_then(|(native_scripts, mut tb)| {
+      let native_scripts = native_scripts.unwrap_or_else(RNativeScripts::new);
+      let interval = validity_interval(&tb);
+      let interval = Some(&interval).filter(|interval| {
+        built || interval.invalid_before.is_some() || interval.invalid_hereafter.is_some()
+      });
+      for i in 0..native_scripts.len() {
+        let signers = native_script_signers(&native_scripts.get(i), &tb.input_types.vkeys, interval)
+          .ok_or_else(|| CError::from("NativeScript can't be satisfied"))?;
+        tb.input_types.vkeys.extend(signers);
+      }
+      Ok(unsafe { std::mem::transmute::<TTransactionBuilder, RTransactionBuilder>(tb) })
+    })
+}
+
+// native_script is None for Plutus script inputs.
 #[no_mangle]
 pub unsafe extern "C" fn cardano_transaction_builder_add_script_input(
-  tb: TransactionBuilder, hash: ScriptHash, input: TransactionInput, amount: Value,
-  result: &mut TransactionBuilder, error: &mut CError,
+  tb: TransactionBuilder, hash: ScriptHash, native_script: COption<NativeScript>,
+  input: TransactionInput, amount: Value, result: &mut TransactionBuilder, error: &mut CError,
 ) -> bool {
   handle_exception_result(|| {
+    let native_script: Option<NativeScript> = native_script.into();
     tb.try_into()
+      .zip(tb.native_scripts())
+      .zip(native_script.map(|native_script| native_script.try_into()).transpose())
       .zip(amount.try_into())
-      .map(|(mut tb, amount): (RTransactionBuilder, RValue)| {
-        tb.add_script_input(&hash.into(), &input.into(), &amount);
-        tb
+      .and_then(|(((tb, native_scripts), native_script), amount)| {
+        add_script_input(tb, native_scripts, hash.into(), native_script, input.into(), amount)
+      })
+      .and_then(|(tb, native_scripts)| {
+        TransactionBuilder::try_from(tb)
+          .and_then(|result| result.with_native_scripts(native_scripts))
       })
-      .and_then(|tb| tb.try_into())
   })
   .response(result, error)
 }
@@ -528,6 +672,7 @@ pub unsafe extern "C" fn cardano_transaction_builder_add_bootstrap_input(
         },
       )
       .and_then(|tb| tb.try_into())
+      .and_then(|result: TransactionBuilder| result.with_native_scripts_of(&tb))
   })
   .response(result, error)
 }
@@ -548,6 +693,7 @@ pub unsafe extern "C" fn cardano_transaction_builder_add_input(
         },
       )
       .and_then(|tb| tb.try_into())
+      .and_then(|result: TransactionBuilder| result.with_native_scripts_of(&tb))
   })
   .response(result, error)
 }
@@ -586,6 +732,7 @@ pub unsafe extern "C" fn cardano_transaction_builder_add_output(
         },
       )
       .and_then(|tb| tb.try_into())
+      .and_then(|result: TransactionBuilder| result.with_native_scripts_of(&tb))
   })
   .response(result, error)
 }
@@ -617,6 +764,7 @@ pub unsafe extern "C" fn cardano_transaction_builder_set_certs(
         tb
       })
       .and_then(|tb| tb.try_into())
+      .and_then(|result: TransactionBuilder| result.with_native_scripts_of(&tb))
   })
   .response(result, error)
 }
@@ -636,6 +784,7 @@ pub unsafe extern "C" fn cardano_transaction_builder_set_withdrawals(
         },
       )
       .and_then(|tb| tb.try_into())
+      .and_then(|result: TransactionBuilder| result.with_native_scripts_of(&tb))
   })
   .response(result, error)
 }
@@ -705,13 +854,22 @@ pub unsafe extern "C" fn cardano_transaction_builder_add_change_if_needed(
   tb: TransactionBuilder, address: Address, result: &mut TransactionBuilderBool, error: &mut CError,
 ) -> bool {
   handle_exception_result(|| {
-    tb.try_into().zip(address.try_into()).and_then(
-      |(mut tb, address): (RTransactionBuilder, RAddress)| {
+    checked_native_scripts(tb, false)
+      .zip(address.try_into())
+      .and_then(|(mut tb, address): (RTransactionBuilder, RAddress)| {
         tb.add_change_if_needed(&address)
           .into_result()
-          .and_then(|result| tb.try_into().map(|tb| TransactionBuilderBool(tb, result)))
-      },
-    )
+          .zip(tb.try_into())
+      })
+      .and_then(|(added, result): (bool, TransactionBuilder)| {
+        let result = TransactionBuilder {
+          config: tb.config,
+          ..result
+        };
+        result
+          .with_native_scripts_of(&tb)
+          .map(|result| TransactionBuilderBool(result, added))
+      })
   })
   .response(result, error)
 }
@@ -721,8 +879,8 @@ pub unsafe extern "C" fn cardano_transaction_builder_build(
   tb: TransactionBuilder, result: &mut TransactionBody, error: &mut CError,
 ) -> bool {
   handle_exception_result(|| {
-    tb.try_into()
-      .and_then(|tb: RTransactionBuilder| tb.build().into_result())
+    checked_native_scripts(tb, true)
+      .and_then(|tb| tb.build().into_result())
       .and_then(|transaction_body| transaction_body.try_into())
   })
   .response(result, error)
@@ -733,13 +891,116 @@ pub unsafe extern "C" fn cardano_transaction_builder_min_fee(
   tb: TransactionBuilder, result: &mut Coin, error: &mut CError,
 ) -> bool {
   handle_exception_result(|| {
-    tb.try_into()
-      .and_then(|tb: RTransactionBuilder| tb.min_fee().into_result())
+    checked_native_scripts(tb, false)
+      .and_then(|tb| tb.min_fee().into_result())
       .map(|fee| from_bignum(&fee))
   })
   .response(result, error)
 }
 
+// Alonzo builder has neither redeemers nor reference scripts, so
+// script_fee and reference_script_fee stay zero until it supports them.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct TransactionBuilderFeeBreakdown {
+  size_fee: Coin,
+  tx_size: u64,
+  fee_per_byte: Coin,
+  fee_constant: Coin,
+  script_fee: Coin,
+  reference_script_fee: Coin,
+  fee: Coin,
+  deposit: Coin,
+  refund: Coin,
+  withdrawals: Coin,
+  change: Value,
+}
+
+impl Free for TransactionBuilderFeeBreakdown {
+  unsafe fn free(&mut self) {
+    self.change.free()
+  }
+}
+
+// native_scripts_size counts the witness bytes CSL's size leaves out.
+fn fee_breakdown(
+  tb: RTransactionBuilder, withdrawals: Option<RWithdrawals>, fee_algo: RLinearFee,
+  native_scripts_size: u64,
+) -> Result<TransactionBuilderFeeBreakdown> {
+  let withdrawals = withdrawals
+    .map(|wls| {
+      let keys = wls.keys();
+      (0..keys.len()).try_fold(to_bignum(0), |acc, i| {
+        acc.checked_add(&wls.get(&keys.get(i)).unwrap_or_else(|| to_bignum(0)))
+      })
+    })
+    .transpose()
+    .into_result()?
+    .unwrap_or_else(|| to_bignum(0));
+  let size_fee = tb.min_fee().into_result()?;
+  let mut sized = tb.clone();
+  sized.set_fee(&to_bignum(0x1_00_00_00_00));
+  let tx_size = sized.full_size().into_result()?;
+  let fee = tb.get_fee_if_set().unwrap_or(size_fee);
+  let deposit = tb.get_deposit().into_result()?;
+  let implicit = tb.get_implicit_input().into_result()?;
+  let refund = implicit.coin().checked_sub(&withdrawals).into_result()?;
+  let input = tb.get_total_input().into_result()?;
+  let required = tb
+    .get_explicit_output()
+    .into_result()
+    .zip(deposit.checked_add(&fee).into_result())
+    .and_then(|(output, spent)| output.checked_add(&RValue::new(&spent)).into_result())?;
+  // An under-funded builder has no change to show.
+  let deficit = required.clamped_sub(&input);
+  if from_bignum(&deficit.coin()) > 0 {
+    let message = format!(
+      "Insufficient input: {} lovelace short of outputs, deposit and fee",
+      from_bignum(&deficit.coin())
+    );
+    return Err(message.into());
+  }
+  if deficit.multiasset().filter(|assets| assets.len() > 0).is_some() {
+    return Err("Insufficient input: assets short of outputs".into());
+  }
+  let change = input.checked_sub(&required).into_result()?;
+  change.try_into().map(|change| TransactionBuilderFeeBreakdown {
+    size_fee: from_bignum(&size_fee),
+    tx_size: tx_size as u64 + native_scripts_size,
+    fee_per_byte: from_bignum(&fee_algo.coefficient()),
+    fee_constant: from_bignum(&fee_algo.constant()),
+    script_fee: 0,
+    reference_script_fee: 0,
+    fee: from_bignum(&fee),
+    deposit: from_bignum(&deposit),
+    refund: from_bignum(&refund),
+    withdrawals: from_bignum(&withdrawals),
+    change,
+  })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_transaction_builder_fee_breakdown(
+  tb: TransactionBuilder, result: &mut TransactionBuilderFeeBreakdown, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    let withdrawals: Option<Withdrawals> = tb.withdrawals.into();
+    let fee_algo = tb.config.fee_algo.into();
+    tb.native_scripts_size()
+      .zip(checked_native_scripts(tb, false))
+      .zip(withdrawals.map(|wls| wls.try_into()).transpose())
+      .and_then(|((size, tb), withdrawals)| fee_breakdown(tb, withdrawals, fee_algo, size))
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_transaction_builder_fee_breakdown_free(
+  fee_breakdown: &mut TransactionBuilderFeeBreakdown,
+) {
+  fee_breakdown.free()
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn cardano_transaction_builder_clone(
   transaction_builder: TransactionBuilder, result: &mut TransactionBuilder, error: &mut CError,
@@ -753,3 +1014,132 @@ pub unsafe extern "C" fn cardano_transaction_builder_free(
 ) {
   transaction_builder.free()
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use cardano_serialization_lib::{
+    address::{EnterpriseAddress as REnterpriseAddress, StakeCredential as RStakeCredential},
+    crypto::{PrivateKey as RPrivateKey, TransactionHash as RTransactionHash, Vkeywitnesses},
+    fees::min_fee,
+    utils::{hash_transaction, make_vkey_witness},
+    ScriptAll as RScriptAll, ScriptPubkey as RScriptPubkey, TimelockExpiry as RTimelockExpiry,
+    Transaction as RTransaction, TransactionBody as RTransactionBody,
+  };
+
+  fn config() -> TransactionBuilderConfig {
+    TransactionBuilderConfig {
+      fee_algo: LinearFee {
+        constant: 155381,
+        coefficient: 44,
+      },
+      pool_deposit: 500000000,
+      key_deposit: 2000000,
+      max_value_size: 5000,
+      max_tx_size: 16384,
+      coins_per_utxo_word: 34482,
+      prefer_pure_change: false,
+    }
+  }
+
+  fn script_address(native_script: &RNativeScript) -> RAddress {
+    let hash = native_script.hash(RScriptHashNamespace::NativeScript);
+    let credential = RStakeCredential::from_scripthash(&hash);
+    REnterpriseAddress::new(0, &credential).to_address()
+  }
+
+  // A builder spending 10 ADA from the script's address into a 2 ADA output.
+  unsafe fn script_input_builder(native_script: &RNativeScript) -> TransactionBuilder {
+    let hash = native_script.hash(RScriptHashNamespace::NativeScript);
+    let input = RTransactionInput::new(&RTransactionHash::from_bytes(vec![0; 32]).unwrap(), 0);
+    let output =
+      RTransactionOutput::new(&script_address(native_script), &RValue::new(&to_bignum(2000000)));
+    let mut error = CError::NullPtr;
+    let mut tb = std::mem::zeroed();
+    assert!(cardano_transaction_builder_new(config(), &mut tb, &mut error));
+    let mut with_input = std::mem::zeroed();
+    assert!(cardano_transaction_builder_add_script_input(
+      tb,
+      hash.try_into().unwrap(),
+      Some(native_script.clone().try_into().unwrap()).into(),
+      input.try_into().unwrap(),
+      RValue::new(&to_bignum(10000000)).try_into().unwrap(),
+      &mut with_input,
+      &mut error,
+    ));
+    let mut with_output = std::mem::zeroed();
+    assert!(cardano_transaction_builder_add_output(
+      with_input,
+      output.try_into().unwrap(),
+      &mut with_output,
+      &mut error,
+    ));
+    with_output
+  }
+
+  // The fee has to cover a signed transaction that carries the input's script.
+  #[test]
+  fn native_script_input_fee() {
+    let key = RPrivateKey::from_normal_bytes(&[7; 32]).unwrap();
+    let native_script =
+      RNativeScript::new_script_pubkey(&RScriptPubkey::new(&key.to_public().hash()));
+    let address = script_address(&native_script);
+    let mut error = CError::NullPtr;
+    unsafe {
+      let with_output = script_input_builder(&native_script);
+      let mut with_change = std::mem::zeroed();
+      assert!(cardano_transaction_builder_add_change_if_needed(
+        with_output,
+        address.try_into().unwrap(),
+        &mut with_change,
+        &mut error,
+      ));
+      let TransactionBuilderBool(with_change, added) = with_change;
+      assert!(added);
+      assert_eq!(with_change.config.fee_algo.constant, 155381);
+      let mut estimated = 0;
+      assert!(cardano_transaction_builder_min_fee(with_change, &mut estimated, &mut error));
+      let mut body = std::mem::zeroed();
+      assert!(cardano_transaction_builder_build(with_change, &mut body, &mut error));
+      let body: RTransactionBody = body.try_into().unwrap();
+
+      let mut vkeys = Vkeywitnesses::new();
+      vkeys.add(&make_vkey_witness(&hash_transaction(&body), &key));
+      let mut native_scripts = RNativeScripts::new();
+      native_scripts.add(&native_script);
+      let mut witness_set = RTransactionWitnessSet::new();
+      witness_set.set_vkeys(&vkeys);
+      witness_set.set_native_scripts(&native_scripts);
+      let signed = RTransaction::new(&body, &witness_set, None);
+      let fee = from_bignum(&min_fee(&signed, &config().fee_algo.into()).unwrap());
+      assert!(estimated >= fee);
+      assert!(from_bignum(&body.fee()) >= fee);
+    }
+  }
+  // Timelocks follow the bounds the builder has when the fee or body is made.
+  #[test]
+  fn native_script_input_timelock() {
+    let key = RPrivateKey::from_normal_bytes(&[7; 32]).unwrap();
+    let mut scripts = RNativeScripts::new();
+    scripts.add(&RNativeScript::new_script_pubkey(&RScriptPubkey::new(
+      &key.to_public().hash(),
+    )));
+    scripts.add(&RNativeScript::new_timelock_expiry(&RTimelockExpiry::new(1000)));
+    let native_script = RNativeScript::new_script_all(&RScriptAll::new(&scripts));
+    let mut error = CError::NullPtr;
+    let mut fee = 0;
+    unsafe {
+      let mut body = std::mem::zeroed();
+      let mut tb = script_input_builder(&native_script);
+      tb.fee = Some(200000).into();
+      assert!(cardano_transaction_builder_min_fee(tb, &mut fee, &mut error));
+      assert!(!cardano_transaction_builder_build(tb, &mut body, &mut error));
+      tb.ttl = Some(2000).into();
+      assert!(!cardano_transaction_builder_min_fee(tb, &mut fee, &mut error));
+      assert!(!cardano_transaction_builder_build(tb, &mut body, &mut error));
+      tb.ttl = Some(1000).into();
+      assert!(cardano_transaction_builder_min_fee(tb, &mut fee, &mut error));
+      assert!(cardano_transaction_builder_build(tb, &mut body, &mut error));
+    }
+  }
+}
@@ -66,3 +66,4 @@ pub unsafe extern "C" fn cardano_transaction_hash_from_bytes(
   })
   .response(result, error)
 }
+
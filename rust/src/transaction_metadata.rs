@@ -2,13 +2,17 @@ use crate::address::pointer::Slot;
 use crate::array::*;
 use crate::data::CData;
 use crate::error::CError;
-use crate::general_transaction_metadata::GeneralTransactionMetadata;
+use crate::general_transaction_metadata::{
+  metadata_from_json, metadata_to_json, GeneralTransactionMetadata, MetadataJsonSchema,
+};
 use crate::option::COption;
 use crate::panic::*;
 use crate::ptr::*;
-use crate::stake_credential::Ed25519KeyHash;
 use crate::stake_credential::ScriptHash;
+use crate::stake_credential::{Ed25519KeyHash, Ed25519KeyHashes};
+use crate::string::{CharPtr, IntoCString};
 use cardano_serialization_lib::{
+  crypto::Ed25519KeyHash as REd25519KeyHash,
   metadata::AuxiliaryData as RAuxiliaryData,
   plutus::{PlutusScript as RPlutusScript, PlutusScripts as RPlutusScripts},
   NativeScript as RNativeScript, NativeScriptKind, NativeScripts as RNativeScripts,
@@ -16,6 +20,8 @@ use cardano_serialization_lib::{
   ScriptNOfK as RScriptNOfK, ScriptPubkey as RScriptPubkey, TimelockExpiry as RTimelockExpiry,
   TimelockStart as RTimelockStart,
 };
+use serde_json::{json, Value as JsonValue};
+use std::collections::BTreeSet;
 use std::convert::{TryFrom, TryInto};
 
 #[repr(C)]
@@ -152,6 +158,438 @@ pub unsafe extern "C" fn cardano_native_script_free(native_script: &mut NativeSc
   native_script.free()
 }
 
+// Bounds follow the transaction body: invalid_before is
+// validity_start_interval, invalid_hereafter is ttl.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct ValidityInterval {
+  invalid_before: COption<Slot>,
+  invalid_hereafter: COption<Slot>,
+}
+
+pub(crate) struct RValidityInterval {
+  pub(crate) invalid_before: Option<Slot>,
+  pub(crate) invalid_hereafter: Option<Slot>,
+}
+
+impl From<ValidityInterval> for RValidityInterval {
+  fn from(interval: ValidityInterval) -> Self {
+    Self {
+      invalid_before: interval.invalid_before.into(),
+      invalid_hereafter: interval.invalid_hereafter.into(),
+    }
+  }
+}
+
+impl From<RValidityInterval> for ValidityInterval {
+  fn from(interval: RValidityInterval) -> Self {
+    Self {
+      invalid_before: interval.invalid_before.into(),
+      invalid_hereafter: interval.invalid_hereafter.into(),
+    }
+  }
+}
+
+fn native_script_children(native_script: &RNativeScript) -> (usize, Vec<RNativeScript>) {
+  let (n, scripts) = match native_script.kind() {
+    NativeScriptKind::ScriptAll => native_script
+      .as_script_all()
+      .map(|all| (None, all.native_scripts())),
+    NativeScriptKind::ScriptAny => native_script
+      .as_script_any()
+      .map(|any| (Some(1), any.native_scripts())),
+    NativeScriptKind::ScriptNOfK => native_script
+      .as_script_n_of_k()
+      .map(|n_of_k| (Some(n_of_k.n() as usize), n_of_k.native_scripts())),
+    _ => None,
+  }
+  .unwrap_or((Some(0), RNativeScripts::new()));
+  let scripts: Vec<RNativeScript> = (0..scripts.len()).map(|i| scripts.get(i)).collect();
+  (n.unwrap_or(scripts.len()), scripts)
+}
+
+// Keeps the search bounded for wide NOfK scripts. Below this many minimal
+// signer sets per script node the result is exact.
+const MAX_SIGNER_OPTIONS: usize = 64;
+
+type Signers = BTreeSet<REd25519KeyHash>;
+
+// Cheapest first, then drops sets which contain a cheaper kept set: those can
+// never be part of a cheaper combination.
+fn prune_signer_options(mut options: Vec<Signers>, available: &Signers) -> Vec<Signers> {
+  options.sort_by_key(|signers| (signers.difference(available).count(), signers.len()));
+  let mut kept: Vec<Signers> = Vec::new();
+  for signers in options {
+    if kept.len() == MAX_SIGNER_OPTIONS {
+      break;
+    }
+    if !kept.iter().any(|cheaper| cheaper.is_subset(&signers)) {
+      kept.push(signers);
+    }
+  }
+  kept
+}
+
+fn combine_signer_options(
+  left: &[Signers], right: &[Signers], available: &Signers,
+) -> Vec<Signers> {
+  let combined = left
+    .iter()
+    .flat_map(|left| right.iter().map(move |right| left.union(right).cloned().collect()))
+    .collect();
+  prune_signer_options(combined, available)
+}
+
+// Minimal signer sets satisfying the script, an empty list when it can't be
+// satisfied.
+fn native_script_signer_options(
+  native_script: &RNativeScript, available: &Signers, interval: Option<&RValidityInterval>,
+) -> Vec<Signers> {
+  match native_script.kind() {
+    NativeScriptKind::ScriptPubkey => native_script
+      .as_script_pubkey()
+      .map(|pubkey| vec![std::iter::once(pubkey.addr_keyhash()).collect()])
+      .unwrap_or_default(),
+    NativeScriptKind::TimelockStart => native_script
+      .as_timelock_start()
+      .filter(|start| {
+        interval
+          .map(|interval| interval.invalid_before.is_some_and(|slot| slot >= start.slot()))
+          .unwrap_or(true)
+      })
+      .map(|_| vec![BTreeSet::new()])
+      .unwrap_or_default(),
+    NativeScriptKind::TimelockExpiry => native_script
+      .as_timelock_expiry()
+      .filter(|expiry| {
+        interval
+          .map(|interval| interval.invalid_hereafter.is_some_and(|slot| slot <= expiry.slot()))
+          .unwrap_or(true)
+      })
+      .map(|_| vec![BTreeSet::new()])
+      .unwrap_or_default(),
+    _ => {
+      let (n, scripts) = native_script_children(native_script);
+      // picked[j]: minimal sets satisfying j of the children seen so far.
+      let mut picked: Vec<Vec<Signers>> = vec![Vec::new(); n + 1];
+      picked[0].push(BTreeSet::new());
+      for script in scripts.iter() {
+        let options = native_script_signer_options(script, available, interval);
+        if options.is_empty() {
+          continue;
+        }
+        for j in (1..=n).rev() {
+          if picked[j - 1].is_empty() {
+            continue;
+          }
+          let mut with_child = combine_signer_options(&picked[j - 1], &options, available);
+          with_child.append(&mut picked[j]);
+          picked[j] = prune_signer_options(with_child, available);
+        }
+      }
+      picked.pop().unwrap_or_default()
+    }
+  }
+}
+
+// Cheapest signer set for the script: the fewest keys outside `available`,
+// then the fewest keys overall. Timelocks are checked against `interval` or
+// ignored when it is None. None means the script can't be satisfied at all.
+pub(crate) fn native_script_signers(
+  native_script: &RNativeScript, available: &Signers, interval: Option<&RValidityInterval>,
+) -> Option<Signers> {
+  native_script_signer_options(native_script, available, interval).into_iter().next()
+}
+
+// Bounds any satisfying transaction has to stay within: All intersects the
+// bounds of its children, Any and NOfK take their hull.
+pub(crate) fn native_script_validity_interval(native_script: &RNativeScript) -> RValidityInterval {
+  match native_script.kind() {
+    NativeScriptKind::ScriptPubkey => RValidityInterval {
+      invalid_before: None,
+      invalid_hereafter: None,
+    },
+    NativeScriptKind::TimelockStart => RValidityInterval {
+      invalid_before: native_script.as_timelock_start().map(|start| start.slot()),
+      invalid_hereafter: None,
+    },
+    NativeScriptKind::TimelockExpiry => RValidityInterval {
+      invalid_before: None,
+      invalid_hereafter: native_script.as_timelock_expiry().map(|expiry| expiry.slot()),
+    },
+    NativeScriptKind::ScriptAll => {
+      let (_, scripts) = native_script_children(native_script);
+      scripts.iter().map(native_script_validity_interval).fold(
+        RValidityInterval {
+          invalid_before: None,
+          invalid_hereafter: None,
+        },
+        |acc, child| RValidityInterval {
+          invalid_before: acc.invalid_before.max(child.invalid_before),
+          invalid_hereafter: match (acc.invalid_hereafter, child.invalid_hereafter) {
+            (Some(acc), Some(child)) => Some(acc.min(child)),
+            (acc, child) => acc.or(child),
+          },
+        },
+      )
+    }
+    _ => {
+      let (n, scripts) = native_script_children(native_script);
+      let intervals: Vec<RValidityInterval> =
+        scripts.iter().map(native_script_validity_interval).collect();
+      if n == 0 || intervals.is_empty() {
+        return RValidityInterval {
+          invalid_before: None,
+          invalid_hereafter: None,
+        };
+      }
+      RValidityInterval {
+        invalid_before: intervals
+          .iter()
+          .map(|interval| interval.invalid_before)
+          .collect::<Option<Vec<Slot>>>()
+          .and_then(|slots| slots.into_iter().min()),
+        invalid_hereafter: intervals
+          .iter()
+          .map(|interval| interval.invalid_hereafter)
+          .collect::<Option<Vec<Slot>>>()
+          .and_then(|slots| slots.into_iter().max()),
+      }
+    }
+  }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct NativeScriptSatisfaction {
+  satisfied: bool,
+  signers: Ed25519KeyHashes,
+  missing: Ed25519KeyHashes,
+}
+
+impl Free for NativeScriptSatisfaction {
+  unsafe fn free(&mut self) {
+    self.signers.free();
+    self.missing.free();
+  }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_native_script_satisfaction(
+  native_script: NativeScript, available: Ed25519KeyHashes, interval: ValidityInterval,
+  result: &mut NativeScriptSatisfaction, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    let available = available.unowned()?;
+    let available: BTreeSet<REd25519KeyHash> =
+      available.iter().map(|hash| (*hash).into()).collect();
+    let interval: RValidityInterval = interval.into();
+    native_script.try_into().and_then(|native_script: RNativeScript| {
+      let (satisfied, signers, missing) =
+        match native_script_signers(&native_script, &available, Some(&interval)) {
+          Some(required) => {
+            let missing: Vec<REd25519KeyHash> = required.difference(&available).cloned().collect();
+            let signers = required.intersection(&available).cloned().collect();
+            (missing.is_empty(), signers, missing)
+          }
+          None => (false, Vec::new(), Vec::new()),
+        };
+      signers
+        .into_iter()
+        .map(|hash| hash.try_into())
+        .collect::<Result<Vec<Ed25519KeyHash>>>()
+        .zip(
+          missing
+            .into_iter()
+            .map(|hash| hash.try_into())
+            .collect::<Result<Vec<Ed25519KeyHash>>>(),
+        )
+        .map(|(signers, missing)| NativeScriptSatisfaction {
+          satisfied,
+          signers: signers.into(),
+          missing: missing.into(),
+        })
+    })
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_native_script_satisfaction_free(
+  satisfaction: &mut NativeScriptSatisfaction,
+) {
+  satisfaction.free()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_native_script_minimal_signers(
+  native_script: NativeScript, result: &mut Ed25519KeyHashes, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    native_script
+      .try_into()
+      .and_then(|native_script: RNativeScript| {
+        native_script_signers(&native_script, &BTreeSet::new(), None)
+          .ok_or_else(|| "NativeScript can't be satisfied".into())
+      })
+      .and_then(|signers| {
+        signers
+          .into_iter()
+          .map(|hash| hash.try_into())
+          .collect::<Result<Vec<Ed25519KeyHash>>>()
+      })
+      .map(|signers| signers.into())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_native_script_validity_interval(
+  native_script: NativeScript, result: &mut ValidityInterval, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    native_script
+      .try_into()
+      .map(|native_script: RNativeScript| native_script_validity_interval(&native_script).into())
+  })
+  .response(result, error)
+}
+
+// cardano-cli simple script JSON: "before" is the expiry slot (ttl),
+// "after" is the start slot (validity_start_interval).
+fn native_script_from_json(json: &JsonValue, path: &str) -> Result<RNativeScript> {
+  let object = json
+    .as_object()
+    .ok_or_else(|| format!("{}: native script must be a JSON object", path))?;
+  let field = |name: &str| {
+    object
+      .get(name)
+      .ok_or_else(|| CError::from(format!("{}: missing \"{}\" field", path, name)))
+  };
+  let slot = || {
+    field("slot")?
+      .as_u64()
+      .ok_or_else(|| CError::from(format!("{}.slot: expected unsigned integer", path)))
+      .and_then(|slot| {
+        Slot::try_from(slot).map_err(|_| format!("{}.slot: {} is out of range", path, slot).into())
+      })
+  };
+  let scripts = || {
+    field("scripts")?
+      .as_array()
+      .ok_or_else(|| CError::from(format!("{}.scripts: expected array", path)))?
+      .iter()
+      .enumerate()
+      .map(|(i, script)| native_script_from_json(script, &format!("{}.scripts[{}]", path, i)))
+      .collect::<Result<Vec<RNativeScript>>>()
+      .map(|scripts| {
+        let mut native_scripts = RNativeScripts::new();
+        for script in scripts.iter() {
+          native_scripts.add(script);
+        }
+        native_scripts
+      })
+  };
+  let kind = field("type")?
+    .as_str()
+    .ok_or_else(|| CError::from(format!("{}.type: expected string", path)))?;
+  match kind {
+    "sig" => field("keyHash")?
+      .as_str()
+      .ok_or_else(|| CError::from(format!("{}.keyHash: expected hex string", path)))
+      .and_then(|key_hash| {
+        hex::decode(key_hash)
+          .map_err(|err| format!("{}.keyHash: {}", path, err).into())
+          .and_then(|bytes| {
+            REd25519KeyHash::from_bytes(bytes)
+              .map_err(|_| format!("{}.keyHash: expected 28 bytes", path).into())
+          })
+      })
+      .map(|key_hash| RNativeScript::new_script_pubkey(&RScriptPubkey::new(&key_hash))),
+    "all" => scripts().map(|scripts| RNativeScript::new_script_all(&RScriptAll::new(&scripts))),
+    "any" => scripts().map(|scripts| RNativeScript::new_script_any(&RScriptAny::new(&scripts))),
+    "atLeast" => field("required")?
+      .as_u64()
+      .and_then(|required| u32::try_from(required).ok())
+      .ok_or_else(|| CError::from(format!("{}.required: expected unsigned integer", path)))
+      .zip(scripts())
+      .map(|(required, scripts)| {
+        RNativeScript::new_script_n_of_k(&RScriptNOfK::new(required, &scripts))
+      }),
+    "before" => slot().map(|slot| RNativeScript::new_timelock_expiry(&RTimelockExpiry::new(slot))),
+    "after" => slot().map(|slot| RNativeScript::new_timelock_start(&RTimelockStart::new(slot))),
+    other => Err(
+      format!(
+        "{}.type: unknown native script type \"{}\", expected one of sig, all, any, atLeast, before, after",
+        path, other
+      )
+      .into(),
+    ),
+  }
+}
+
+fn native_script_to_json(native_script: &RNativeScript) -> JsonValue {
+  let scripts = |scripts: RNativeScripts| {
+    JsonValue::Array(
+      (0..scripts.len())
+        .map(|i| native_script_to_json(&scripts.get(i)))
+        .collect(),
+    )
+  };
+  match native_script.kind() {
+    NativeScriptKind::ScriptPubkey => native_script.as_script_pubkey().map(|pubkey| {
+      json!({ "type": "sig", "keyHash": hex::encode(pubkey.addr_keyhash().to_bytes()) })
+    }),
+    NativeScriptKind::ScriptAll => native_script
+      .as_script_all()
+      .map(|all| json!({ "type": "all", "scripts": scripts(all.native_scripts()) })),
+    NativeScriptKind::ScriptAny => native_script
+      .as_script_any()
+      .map(|any| json!({ "type": "any", "scripts": scripts(any.native_scripts()) })),
+    NativeScriptKind::ScriptNOfK => native_script.as_script_n_of_k().map(|n_of_k| {
+      json!({
+        "type": "atLeast",
+        "required": n_of_k.n(),
+        "scripts": scripts(n_of_k.native_scripts())
+      })
+    }),
+    NativeScriptKind::TimelockStart => native_script
+      .as_timelock_start()
+      .map(|start| json!({ "type": "after", "slot": start.slot() })),
+    NativeScriptKind::TimelockExpiry => native_script
+      .as_timelock_expiry()
+      .map(|expiry| json!({ "type": "before", "slot": expiry.slot() })),
+  }
+  .unwrap_or(JsonValue::Null)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_native_script_from_json(
+  json: CharPtr, result: &mut NativeScript, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    json
+      .unowned()
+      .and_then(|json| serde_json::from_str(json).into_result())
+      .and_then(|json: JsonValue| native_script_from_json(&json, "$"))
+      .and_then(|native_script| native_script.try_into())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_native_script_to_json(
+  native_script: NativeScript, result: &mut CharPtr, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    native_script
+      .try_into()
+      .map(|native_script: RNativeScript| native_script_to_json(&native_script).to_string())
+      .map(|json| json.into_cstr())
+  })
+  .response(result, error)
+}
+
 pub type NativeScripts = CArray<NativeScript>;
 
 impl TryFrom<NativeScripts> for RNativeScripts {
@@ -562,6 +1000,39 @@ pub unsafe extern "C" fn cardano_auxiliary_data_from_bytes(
   .response(result, error)
 }
 
+// Auxiliary data holding only the metadata of a cardano-cli metadata JSON file.
+#[no_mangle]
+pub unsafe extern "C" fn cardano_auxiliary_data_from_metadata_json(
+  json: CharPtr, schema: MetadataJsonSchema, result: &mut AuxiliaryData, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    json.unowned().and_then(|json| metadata_from_json(json, schema)).and_then(|metadata| {
+      let mut auxiliary_data = RAuxiliaryData::new();
+      auxiliary_data.set_metadata(&metadata);
+      auxiliary_data.try_into()
+    })
+  })
+  .response(result, error)
+}
+
+// Scripts are not part of the metadata JSON, data without metadata gives "{}".
+#[no_mangle]
+pub unsafe extern "C" fn cardano_auxiliary_data_metadata_to_json(
+  auxiliary_data: AuxiliaryData, schema: MetadataJsonSchema, result: &mut CharPtr,
+  error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    auxiliary_data
+      .try_into()
+      .and_then(|auxiliary_data: RAuxiliaryData| match auxiliary_data.metadata() {
+        Some(metadata) => metadata_to_json(&metadata, schema),
+        None => Ok("{}".to_string()),
+      })
+      .map(|json| json.into_cstr())
+  })
+  .response(result, error)
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn cardano_auxiliary_data_clone(
   auxiliary_data: AuxiliaryData, result: &mut AuxiliaryData, error: &mut CError,
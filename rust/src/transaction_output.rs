@@ -1,10 +1,12 @@
 use crate::address::address::Address;
 use crate::array::CArray;
+use crate::cip5;
 use crate::data::CData;
 use crate::error::CError;
 use crate::option::COption;
 use crate::panic::*;
 use crate::ptr::*;
+use crate::string::{CharPtr, IntoCString};
 use crate::value::Value;
 use cardano_serialization_lib::{
   crypto::DataHash as RDataHash, TransactionOutput as RTransactionOutput,
@@ -52,6 +54,31 @@ pub unsafe extern "C" fn cardano_data_hash_from_bytes(
   .response(result, error)
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn cardano_data_hash_to_bech32(
+  data_hash: DataHash, result: &mut CharPtr, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    let data_hash: RDataHash = data_hash.into();
+    cip5::encode(cip5::DATUM, &data_hash.to_bytes()).map(|bech32| bech32.into_cstr())
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_data_hash_from_bech32(
+  bech32: CharPtr, result: &mut DataHash, error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    bech32
+      .unowned()
+      .and_then(|bech32| cip5::decode(cip5::DATUM, bech32))
+      .and_then(|bytes| RDataHash::from_bytes(bytes).into_result())
+      .map(|data_hash| data_hash.into())
+  })
+  .response(result, error)
+}
+
 #[repr(C)]
 #[derive(Copy, Clone)]
 pub struct TransactionOutput {
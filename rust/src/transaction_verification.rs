@@ -0,0 +1,161 @@
+use crate::address::byron::ByronAddress;
+use crate::array::CArray;
+use crate::bootstrap_witness::BootstrapWitnesses;
+use crate::error::CError;
+use crate::panic::*;
+use crate::ptr::*;
+use crate::stake_credential::{Ed25519KeyHash, Ed25519KeyHashes};
+use crate::transaction::Transaction;
+use crate::transaction_metadata::{NativeScript, NativeScripts};
+use crate::transaction_balance::required_signers;
+use crate::transaction_unspent_output::TransactionUnspentOutputs;
+use crate::vkeywitness::Vkeywitnesses;
+use cardano_serialization_lib::{
+  address::ByronAddress as RByronAddress,
+  crypto::BootstrapWitness as RBootstrapWitness,
+  legacy_address::{Addr, AddressMatchXPub},
+  utils::{hash_transaction, TransactionUnspentOutputs as RTransactionUnspentOutputs},
+  Transaction as RTransaction, TransactionInputs as RTransactionInputs,
+  TransactionOutput as RTransactionOutput,
+};
+use std::collections::BTreeSet;
+use std::convert::{TryFrom, TryInto};
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct TransactionVerification {
+  valid: bool,
+  invalid_vkeywitnesses: Vkeywitnesses,
+  invalid_bootstrap_witnesses: BootstrapWitnesses,
+  missing_signers: Ed25519KeyHashes,
+  missing_byron_addresses: CArray<ByronAddress>,
+  // Witness set native scripts failing on their keys or timelocks.
+  unsatisfied_native_scripts: NativeScripts,
+}
+
+impl Free for TransactionVerification {
+  unsafe fn free(&mut self) {
+    self.invalid_vkeywitnesses.free();
+    self.invalid_bootstrap_witnesses.free();
+    self.missing_signers.free();
+    self.missing_byron_addresses.free();
+    self.unsatisfied_native_scripts.free();
+  }
+}
+
+fn resolve(
+  inputs: &RTransactionInputs, utxos: &RTransactionUnspentOutputs,
+) -> Result<Vec<RTransactionOutput>> {
+  (0..inputs.len())
+    .map(|i| {
+      let input = inputs.get(i);
+      (0..utxos.len())
+        .map(|j| utxos.get(j))
+        .find(|utxo| utxo.input() == input)
+        .map(|utxo| utxo.output())
+        .ok_or_else(|| {
+          format!(
+            "Input {}#{} is not in the provided UTxO set",
+            hex::encode(input.transaction_id().to_bytes()),
+            input.index()
+          )
+          .into()
+        })
+    })
+    .collect()
+}
+
+// The address root is recomputed from the witness key, chain code and attributes.
+fn bootstrap_covers(address: &RByronAddress, witness: &RBootstrapWitness) -> bool {
+  let mut xpub = witness.vkey().public_key().as_bytes();
+  xpub.extend(witness.chain_code());
+  witness.attributes() == address.attributes()
+    && Addr::try_from(address.to_bytes().as_slice())
+      .map(|addr| addr.identical_with_pubkey_raw(&xpub) == AddressMatchXPub::Yes)
+      .unwrap_or(false)
+}
+
+fn verify(tx: &RTransaction, utxos: &RTransactionUnspentOutputs) -> Result<TransactionVerification> {
+  let body = tx.body();
+  let witness_set = tx.witness_set();
+  let tx_hash = hash_transaction(&body).to_bytes();
+  let mut spent = resolve(&body.inputs(), utxos)?;
+  if let Some(collateral) = body.collateral() {
+    spent.extend(resolve(&collateral, utxos)?);
+  }
+
+  let mut signed = BTreeSet::new();
+  let mut invalid_vkeywitnesses = Vec::new();
+  if let Some(vkeys) = witness_set.vkeys() {
+    for i in 0..vkeys.len() {
+      let witness = vkeys.get(i);
+      let public_key = witness.vkey().public_key();
+      if public_key.verify(&tx_hash, &witness.signature()) {
+        signed.insert(public_key.hash());
+      } else {
+        invalid_vkeywitnesses.push(witness);
+      }
+    }
+  }
+  let mut bootstraps = Vec::new();
+  let mut invalid_bootstrap_witnesses = Vec::new();
+  if let Some(witnesses) = witness_set.bootstraps() {
+    for i in 0..witnesses.len() {
+      let witness = witnesses.get(i);
+      if witness.vkey().public_key().verify(&tx_hash, &witness.signature()) {
+        bootstraps.push(witness);
+      } else {
+        invalid_bootstrap_witnesses.push(witness);
+      }
+    }
+  }
+
+  let (required, byrons, unsatisfied) = required_signers(&body, &witness_set, &spent, &signed);
+  let missing_signers = required
+    .difference(&signed)
+    .cloned()
+    .map(|hash| hash.try_into())
+    .collect::<Result<Vec<Ed25519KeyHash>>>()?;
+  let missing_byron_addresses: Vec<RByronAddress> = byrons
+    .into_iter()
+    .filter(|byron| !bootstraps.iter().any(|witness| bootstrap_covers(byron, witness)))
+    .collect();
+
+  Ok(TransactionVerification {
+    valid: invalid_vkeywitnesses.is_empty()
+      && invalid_bootstrap_witnesses.is_empty()
+      && missing_signers.is_empty()
+      && missing_byron_addresses.is_empty()
+      && unsatisfied.is_empty(),
+    invalid_vkeywitnesses: invalid_vkeywitnesses.into(),
+    invalid_bootstrap_witnesses: invalid_bootstrap_witnesses.into(),
+    missing_signers: missing_signers.into(),
+    missing_byron_addresses: missing_byron_addresses.into(),
+    unsatisfied_native_scripts: unsatisfied
+      .into_iter()
+      .map(|script| script.try_into())
+      .collect::<Result<Vec<NativeScript>>>()?
+      .into(),
+  })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_transaction_verify(
+  transaction: Transaction, utxos: TransactionUnspentOutputs, result: &mut TransactionVerification,
+  error: &mut CError,
+) -> bool {
+  handle_exception_result(|| {
+    transaction
+      .try_into()
+      .zip(utxos.try_into())
+      .and_then(|(tx, utxos)| verify(&tx, &utxos))
+  })
+  .response(result, error)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn cardano_transaction_verification_free(
+  verification: &mut TransactionVerification,
+) {
+  verification.free()
+}